@@ -0,0 +1,161 @@
+use gtk::glib;
+use gtk::glib::prelude::*;
+use gtk::glib::subclass::prelude::*;
+
+use crate::hypr::hyprctl::Monitor;
+use crate::hypr::hyprpaper::{HyprpaperConfig, WallpaperEntry};
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+    use std::sync::OnceLock;
+
+    use gtk::glib;
+    use gtk::glib::Properties;
+    use gtk::glib::prelude::*;
+    use gtk::glib::subclass::Signal;
+    use gtk::glib::subclass::prelude::*;
+
+    use crate::hypr::hyprctl::Monitor;
+    use crate::hypr::hyprpaper::{HyprpaperConfig, WallpaperEntry};
+    use crate::settings::{MAX_THUMBNAIL_SIZE, MIN_THUMBNAIL_SIZE, get_settings, update_settings};
+
+    #[derive(Properties, Default)]
+    #[properties(wrapper_type = super::AppModel)]
+    pub struct AppModel {
+        #[property(get, set = Self::set_selected_monitor)]
+        pub selected_monitor: RefCell<String>,
+        // Every control for the thumbnail size is bound to this, so they all move together.
+        #[property(get, set = Self::set_thumbnail_size)]
+        pub thumbnail_size: Cell<i32>,
+
+        pub config: RefCell<Option<HyprpaperConfig>>,
+        pub monitors: RefCell<Vec<Monitor>>,
+        pub preview: RefCell<Option<WallpaperEntry>>,
+    }
+
+    impl AppModel {
+        fn set_selected_monitor(&self, monitor: String) {
+            if *self.selected_monitor.borrow() == monitor {
+                return;
+            }
+            self.selected_monitor.replace(monitor.clone());
+            self.obj().notify_selected_monitor();
+            self.obj().emit_by_name::<()>("selected-monitor-changed", &[&monitor]);
+        }
+
+        fn set_thumbnail_size(&self, size: i32) {
+            let size = size.clamp(MIN_THUMBNAIL_SIZE, MAX_THUMBNAIL_SIZE);
+            if self.thumbnail_size.get() == size {
+                return;
+            }
+            self.thumbnail_size.set(size);
+            update_settings(|settings| settings.thumbnail_size = size);
+            self.obj().notify_thumbnail_size();
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for AppModel {
+        const NAME: &'static str = "UltrapaperAppModel";
+        type Type = super::AppModel;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for AppModel {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.thumbnail_size.set(get_settings().thumbnail_size);
+        }
+
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: OnceLock<Vec<Signal>> = OnceLock::new();
+            SIGNALS.get_or_init(|| {
+                vec![
+                    Signal::builder("monitors-changed").build(),
+                    Signal::builder("wallpaper-changed").build(),
+                    Signal::builder("selected-monitor-changed")
+                        .param_types([String::static_type()])
+                        .build(),
+                ]
+            })
+        }
+    }
+}
+
+glib::wrapper! {
+    pub struct AppModel(ObjectSubclass<imp::AppModel>);
+}
+
+thread_local! {
+    static APP_MODEL: AppModel = glib::Object::new();
+}
+
+// GObjects stay on the thread that made them, all state is read and written from the main thread.
+pub fn app_model() -> AppModel {
+    APP_MODEL.with(|model| model.clone())
+}
+
+impl AppModel {
+    // Signals are emitted after every borrow is released, so handlers can read the model again.
+    pub fn set_config(&self, config: HyprpaperConfig) {
+        self.imp().config.replace(Some(config));
+        self.emit_by_name::<()>("wallpaper-changed", &[]);
+    }
+
+    pub fn read_config<T: Default>(&self, read: impl FnOnce(&HyprpaperConfig) -> T) -> T {
+        self.imp().config.borrow().as_ref().map(read).unwrap_or_default()
+    }
+
+    pub fn update_config(&self, update: impl FnOnce(&mut HyprpaperConfig)) {
+        if let Some(config) = self.imp().config.borrow_mut().as_mut() {
+            update(config);
+        }
+    }
+
+    pub fn save_config(&self) {
+        self.update_config(|config| config.save_config());
+        self.emit_by_name::<()>("wallpaper-changed", &[]);
+    }
+
+    pub fn monitors(&self) -> Vec<Monitor> {
+        self.imp().monitors.borrow().clone()
+    }
+
+    pub fn with_monitors<T>(&self, read: impl FnOnce(&[Monitor]) -> T) -> T {
+        read(&self.imp().monitors.borrow())
+    }
+
+    pub fn set_monitors(&self, monitors: Vec<Monitor>) {
+        self.imp().monitors.replace(monitors);
+        self.emit_by_name::<()>("monitors-changed", &[]);
+    }
+
+    pub fn preview(&self) -> Option<WallpaperEntry> {
+        self.imp().preview.borrow().clone()
+    }
+
+    pub fn set_preview(&self, preview: Option<WallpaperEntry>) {
+        self.imp().preview.replace(preview);
+    }
+
+    pub fn connect_monitors_changed(&self, f: impl Fn(&Self) + 'static) -> glib::SignalHandlerId {
+        self.connect_local("monitors-changed", false, move |values| {
+            f(&values[0].get::<Self>().ok()?);
+            None
+        })
+    }
+
+    pub fn connect_wallpaper_changed(&self, f: impl Fn(&Self) + 'static) -> glib::SignalHandlerId {
+        self.connect_local("wallpaper-changed", false, move |values| {
+            f(&values[0].get::<Self>().ok()?);
+            None
+        })
+    }
+
+    pub fn connect_selected_monitor_changed(&self, f: impl Fn(&Self, &str) + 'static) -> glib::SignalHandlerId {
+        self.connect_local("selected-monitor-changed", false, move |values| {
+            f(&values[0].get::<Self>().ok()?, &values[1].get::<String>().ok()?);
+            None
+        })
+    }
+}
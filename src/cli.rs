@@ -0,0 +1,113 @@
+use std::path::PathBuf;
+
+use gio::prelude::*;
+use gio::{Application, Cancellable};
+use gtk::glib::{self, ExitCode};
+
+use crate::APP_ID;
+use crate::hypr::hyprctl::try_get_monitors;
+use crate::hypr::hyprpaper::HyprpaperConfig;
+use crate::hypr::mpvpaper;
+use crate::profiles::{apply_profile, delete_profile, find_profile_for_monitors, list_profiles, save_profile};
+use crate::state::{set_config, set_monitors};
+
+// Returns the exit code when the arguments name a command line action, and None to start the GUI.
+pub fn run(args: &[String]) -> Option<ExitCode> {
+    match args.get(1).map(String::as_str) {
+        Some("check") => Some(check(args.get(2).map(PathBuf::from))),
+        Some("profile") => Some(profile(&args[2..])),
+        Some("video") => Some(video(args.get(2).map(String::as_str))),
+        _ => None,
+    }
+}
+
+fn video(command: Option<&str>) -> ExitCode {
+    match command {
+        Some("start") => mpvpaper::start_saved(),
+        Some("pause") => mpvpaper::set_paused(true),
+        Some("resume") => mpvpaper::set_paused(false),
+        _ => {
+            eprintln!("Usage: ultrapaper video [start | pause | resume]");
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+fn profile(args: &[String]) -> ExitCode {
+    let name = args.get(1).map(String::as_str);
+    let result = match (args.first().map(String::as_str), name) {
+        (Some("list") | None, _) => {
+            for name in list_profiles() {
+                println!("{}", name);
+            }
+            Ok(())
+        }
+        (Some("save"), Some(name)) => HyprpaperConfig::new(HyprpaperConfig::get_default_config_path())
+            .map_err(|err| format!("Error loading config: {}", err))
+            .and_then(|config| save_profile(name, &config.wallpapers)),
+        (Some("delete"), Some(name)) => delete_profile(name),
+        (Some("apply"), Some(name)) => apply(name),
+        (Some("auto"), _) => try_get_monitors()
+            .and_then(|monitors| find_profile_for_monitors(&monitors).ok_or("No profile matches the connected monitors".to_string()))
+            .and_then(|name| apply(&name)),
+        _ => Err("Usage: ultrapaper profile [list | save NAME | apply NAME | delete NAME | auto]".to_string()),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// A running Ultrapaper applies the profile itself, so its view of the config stays current.
+fn apply(name: &str) -> Result<(), String> {
+    let app = Application::builder().application_id(APP_ID).build();
+    if app.register(None::<&Cancellable>).is_ok() && app.is_remote() {
+        app.activate_action("apply-profile", Some(&name.to_variant()));
+        if let Some(connection) = app.dbus_connection() {
+            let _ = connection.flush_sync(None::<&Cancellable>);
+        }
+        return Ok(());
+    }
+
+    let config = HyprpaperConfig::new(HyprpaperConfig::get_default_config_path())
+        .map_err(|err| format!("Error loading config: {}", err))?;
+    set_config(config);
+    set_monitors(try_get_monitors()?);
+
+    // The apply runs on the main context, which is driven here until it, the hooks and the hyprlock
+    // sync are done.
+    let post_apply = apply_profile(name)?;
+    glib::MainContext::default().block_on(post_apply.finished());
+    Ok(())
+}
+
+fn check(path: Option<PathBuf>) -> ExitCode {
+    let path = path.unwrap_or_else(HyprpaperConfig::get_default_config_path);
+    let config = match HyprpaperConfig::new(path.clone()) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}: {}", path.display(), err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // Outside of Hyprland the monitor names can't be checked, everything else still is.
+    let monitors = try_get_monitors().ok().filter(|monitors| !monitors.is_empty());
+    let diagnostics = config.validate(monitors.as_deref());
+
+    for diagnostic in &diagnostics {
+        println!("{}", diagnostic);
+    }
+
+    if diagnostics.is_empty() {
+        println!("{}: no problems found", path.display());
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
@@ -0,0 +1,37 @@
+use gio::{AppInfo, BusType, Cancellable, DBusCallFlags, File};
+use gtk::prelude::*;
+
+pub fn open_in_image_viewer(path: &str, context: &impl IsA<gio::AppLaunchContext>) {
+    let uri = File::for_path(path).uri();
+    if let Err(err) = AppInfo::launch_default_for_uri(&uri, Some(context)) {
+        eprintln!("Could not open {}: {}", path, err);
+    }
+}
+
+pub fn show_in_file_manager(path: &str) {
+    let connection = match gio::bus_get_sync(BusType::Session, None::<&Cancellable>) {
+        Ok(connection) => connection,
+        Err(err) => {
+            eprintln!("Could not connect to the session bus: {}", err);
+            return;
+        }
+    };
+
+    let uris = vec![File::for_path(path).uri().to_string()];
+    connection.call(
+        Some("org.freedesktop.FileManager1"),
+        "/org/freedesktop/FileManager1",
+        "org.freedesktop.FileManager1",
+        "ShowItems",
+        Some(&(uris, "").to_variant()),
+        None,
+        DBusCallFlags::NONE,
+        -1,
+        None::<&Cancellable>,
+        |result| {
+            if let Err(err) = result {
+                eprintln!("Could not show the file in the file manager: {}", err);
+            }
+        },
+    );
+}
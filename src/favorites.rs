@@ -0,0 +1,47 @@
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::fs;
+
+use crate::paths::{config_dir, favorites_file};
+
+thread_local! {
+    static FAVORITES: RefCell<Option<BTreeSet<String>>> = const { RefCell::new(None) };
+}
+
+fn with_favorites<T>(f: impl FnOnce(&mut BTreeSet<String>) -> T) -> T {
+    FAVORITES.with(|favorites| {
+        let mut favorites = favorites.borrow_mut();
+        let favorites = favorites.get_or_insert_with(|| {
+            fs::read_to_string(favorites_file())
+                .map(|content| {
+                    content
+                        .lines()
+                        .map(str::trim)
+                        .filter(|line| !line.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default()
+        });
+        f(favorites)
+    })
+}
+
+pub fn is_favorite(path: &str) -> bool {
+    with_favorites(|favorites| favorites.contains(path))
+}
+
+pub fn set_favorite(path: &str, favorite: bool) {
+    with_favorites(|favorites| {
+        if favorite {
+            favorites.insert(path.to_owned());
+        } else {
+            favorites.remove(path);
+        }
+
+        let content: String = favorites.iter().map(|path| format!("{}\n", path)).collect();
+        if let Err(err) = fs::create_dir_all(config_dir()).and_then(|_| fs::write(favorites_file(), content)) {
+            eprintln!("Could not save favorites: {}", err);
+        }
+    });
+}
@@ -0,0 +1,133 @@
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Write};
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use gtk::glib;
+
+use crate::paths::{cache_dir, hooks_log_file};
+use crate::settings::get_settings;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+enum HookResult {
+    Exited(ExitStatus),
+    TimedOut,
+    Failed(std::io::Error),
+}
+
+// Hooks run one after the other on the post-apply worker. Each one gets its own process group, so
+// whatever it started in the background is killed with it once the timeout is reached.
+pub fn run_hooks(monitor: &str, path: &str) {
+    let settings = get_settings();
+    let timeout = Duration::from_secs(settings.hook_timeout_seconds);
+
+    for hook in &settings.hooks {
+        let child = Command::new("sh")
+            .arg("-c")
+            .arg(hook)
+            .env("ULTRAPAPER_MONITOR", monitor)
+            .env("ULTRAPAPER_PATH", path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .process_group(0)
+            .spawn();
+
+        let (result, output) = match child {
+            Ok(mut child) => {
+                let readers = [read_in_background(child.stdout.take()), read_in_background(child.stderr.take())];
+                let result = wait_with_timeout(&mut child, &readers, timeout);
+                // A process that left the group may still hold a pipe, its output is given up on.
+                let output = readers
+                    .into_iter()
+                    .filter(|reader| reader.is_finished())
+                    .map(|reader| reader.join().unwrap_or_default())
+                    .collect();
+                (result, output)
+            }
+            Err(err) => (HookResult::Failed(err), String::new()),
+        };
+
+        log_hook(hook, &result, &output);
+    }
+}
+
+// Pipes are drained on their own threads so a chatty hook can't block on a full pipe.
+fn read_in_background(pipe: Option<impl Read + Send + 'static>) -> JoinHandle<String> {
+    thread::spawn(move || {
+        let mut output = String::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_string(&mut output);
+        }
+        output
+    })
+}
+
+// A hook is done once the shell has exited and nothing it started still holds its output open.
+fn wait_with_timeout(child: &mut Child, readers: &[JoinHandle<String>], timeout: Duration) -> HookResult {
+    let started = Instant::now();
+    let mut status = None;
+    loop {
+        if status.is_none() {
+            match child.try_wait() {
+                Ok(exited) => status = exited,
+                Err(err) => {
+                    kill_process_group(child);
+                    return HookResult::Failed(err);
+                }
+            }
+        }
+        if let Some(status) = status
+            && readers.iter().all(|reader| reader.is_finished())
+        {
+            return HookResult::Exited(status);
+        }
+        if started.elapsed() >= timeout {
+            kill_process_group(child);
+            // Gives the readers a moment to see their pipes close.
+            thread::sleep(POLL_INTERVAL);
+            return HookResult::TimedOut;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn kill_process_group(child: &mut Child) {
+    // The group id is the shell's pid, `process_group(0)` made it the group leader.
+    unsafe {
+        libc::killpg(child.id() as libc::pid_t, libc::SIGKILL);
+    }
+    let _ = child.wait();
+}
+
+fn log_hook(hook: &str, result: &HookResult, output: &str) {
+    let status = match result {
+        HookResult::Exited(status) => status.to_string(),
+        HookResult::TimedOut => "timed out".to_string(),
+        HookResult::Failed(err) => format!("failed to start: {}", err),
+    };
+    let timestamp = glib::DateTime::now_local()
+        .and_then(|now| now.format_iso8601())
+        .map(|now| now.to_string())
+        .unwrap_or_default();
+
+    let mut entry = format!("[{}] {}: {}\n", timestamp, hook, status);
+    entry.push_str(output);
+    if !output.is_empty() && !output.ends_with('\n') {
+        entry.push('\n');
+    }
+
+    let result = fs::create_dir_all(cache_dir()).and_then(|_| {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(hooks_log_file())?
+            .write_all(entry.as_bytes())
+    });
+    if let Err(err) = result {
+        eprintln!("Could not write the hook log: {}", err);
+    }
+}
@@ -0,0 +1,32 @@
+use std::env;
+use std::path::PathBuf;
+
+use gio::glib;
+use gio::prelude::*;
+
+// Hyprland announces every change on this socket, one `event>>data` line each.
+fn event_socket_path() -> Option<PathBuf> {
+    let instance = env::var_os("HYPRLAND_INSTANCE_SIGNATURE")?;
+    Some(glib::user_runtime_dir().join("hypr").join(instance).join(".socket2.sock"))
+}
+
+// Calls on_event with the name of every event until Hyprland closes the socket.
+pub async fn listen(on_event: impl Fn(&str)) -> Result<(), String> {
+    let path = event_socket_path().ok_or("HYPRLAND_INSTANCE_SIGNATURE is not set")?;
+    let connection = gio::SocketClient::new()
+        .connect_future(&gio::UnixSocketAddress::new(&path))
+        .await
+        .map_err(|err| format!("Could not connect to {}: {}", path.display(), err))?;
+
+    let input = gio::DataInputStream::new(&connection.input_stream());
+    while let Some(line) = input
+        .read_line_utf8_future(glib::Priority::DEFAULT)
+        .await
+        .map_err(|err| format!("Could not read Hyprland events: {}", err))?
+    {
+        if let Some((event, _)) = line.split_once(">>") {
+            on_event(event);
+        }
+    }
+    Ok(())
+}
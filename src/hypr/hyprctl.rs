@@ -1,15 +1,65 @@
+use std::cell::{Cell, RefCell};
 use std::process::Command;
+use std::sync::mpsc::Sender;
+
+use gio::glib::{self, JoinHandle};
 
 use crate::{
-    hypr::hyprpaper::WallpaperEntry,
+    hypr::{
+        hyprpaper::{FitMode, WallpaperEntry},
+        mpvpaper,
+    },
+    images::{scan::is_animated, transcode::backend_path},
+    post_apply::{PostApply, send_post_apply},
+    settings::{VideoBackend, get_settings},
     state::{
-        add_wallpaper, get_selected_monitor, has_more_than_one_wallpaper, has_more_wallpapers_than_monitors, remove_wallpaper_of_monitor, save_config, set_preloads_from_wallpapers, set_wallpapers
+        add_wallpaper, get_monitor_details, get_monitor_identifier, get_monitors as get_state_monitors, get_preview, get_selected_monitor, get_wallpapers, has_more_than_one_wallpaper, has_more_wallpapers_than_monitors, remove_wallpaper_of_monitor, save_config, set_preloads_from_wallpapers, set_preview, set_wallpapers
     },
 };
 
-pub fn set_wallpaper(path: String) {
+thread_local! {
+    // Images are converted in the background, wallpapers still change in the order they were set.
+    static LAST_APPLY: RefCell<Option<JoinHandle<()>>> = const { RefCell::new(None) };
+    // Every change bumps it, so a preview that finishes converting late is dropped.
+    static PREVIEW_REQUEST: Cell<u64> = const { Cell::new(0) };
+}
+
+pub fn set_wallpaper(monitor: String, path: String) -> PostApply {
+    set_wallpaper_with_mode(monitor, path, get_settings().fit_mode)
+}
+
+pub fn set_wallpaper_with_mode(monitor: String, path: String, mode: FitMode) -> PostApply {
+    set_preview(None);
+    PREVIEW_REQUEST.set(PREVIEW_REQUEST.get() + 1);
+
+    let (done, post_apply) = PostApply::pending();
+    let source = path.clone();
+    let render = gio::spawn_blocking(move || (is_animated(&source), renderable_path(source)));
+    queue_apply(render, move |rendered| {
+        let (animated, shown_path) = rendered.unwrap_or_else(|_| (false, path.clone()));
+        show_wallpaper(monitor, path, shown_path, animated, mode, done);
+    });
+    post_apply
+}
+
+// Every apply is rendered off the UI thread as soon as it comes in, the results are still shown
+// in the order the applies were made.
+fn queue_apply<T: 'static>(render: impl Future<Output = T> + 'static, show: impl FnOnce(T) + 'static) {
+    let previous = LAST_APPLY.take();
+    let apply = glib::spawn_future_local(async move {
+        let rendered = render.await;
+        if let Some(previous) = previous {
+            let _ = previous.await;
+        }
+        show(rendered);
+    });
+    LAST_APPLY.set(Some(apply));
+}
+
+fn show_wallpaper(monitor: String, source: String, path: String, animated: bool, mode: FitMode, done: Sender<()>) {
+    let animated = (get_settings().video_backend == VideoBackend::Mpvpaper && animated).then_some(source);
+
     let had_more_than_one_wallpaper = has_more_than_one_wallpaper();
-    let monitor = get_selected_monitor();
 
     if monitor.is_empty() {
         set_wallpapers(vec![]);
@@ -17,7 +67,7 @@ pub fn set_wallpaper(path: String) {
         remove_wallpaper_of_monitor(monitor.clone());
     }
 
-    let entry = WallpaperEntry::new(monitor.clone(), path.clone());
+    let entry = WallpaperEntry::new(get_monitor_identifier(&monitor), path.clone()).with_mode(mode);
     add_wallpaper(entry);
 
 
@@ -36,42 +86,287 @@ pub fn set_wallpaper(path: String) {
             .arg("hyprctl dispatch exec hyprpaper")
             .output();
     } else {
-        let _ = Command::new("sh")
-            .arg("-c")
-            .arg(format!("hyprctl hyprpaper preload {}", &path))
-            .output();
-        let _ = Command::new("sh")
-            .arg("-c")
-            .arg(format!("hyprctl hyprpaper wallpaper {},{}", monitor, path))
-            .output();
+        hyprpaper("preload", &path);
+        hyprpaper("wallpaper", &WallpaperEntry::new(monitor.clone(), path.clone()).with_mode(mode).value());
+    }
+    hyprpaper("unload", "unused");
+
+    // hyprpaper keeps showing the still frame underneath, the player's surface covers it.
+    match animated {
+        Some(video) => mpvpaper::play(&monitor, &video),
+        None => mpvpaper::stop(&monitor),
+    }
+
+    send_post_apply(vec![(monitor, path)], done);
+}
+
+// Replaces every assignment at once, as switching to a profile does.
+pub fn apply_wallpapers(wallpapers: Vec<WallpaperEntry>) -> PostApply {
+    set_preview(None);
+    PREVIEW_REQUEST.set(PREVIEW_REQUEST.get() + 1);
+
+    let monitors = get_monitor_details();
+    let play_animations = get_settings().video_backend == VideoBackend::Mpvpaper;
+    let (done, post_apply) = PostApply::pending();
+    let render = gio::spawn_blocking(move || {
+        let mut players = Vec::new();
+        let wallpapers: Vec<WallpaperEntry> = wallpapers
+            .into_iter()
+            .map(|entry| {
+                // The still frame of an animation that was playing stands for the animation itself.
+                let source = mpvpaper::source_path(&entry.path).unwrap_or_else(|| entry.path.clone());
+                if play_animations && is_animated(&source) {
+                    let output = monitors
+                        .iter()
+                        .find(|monitor| monitor.matches(&entry.monitor))
+                        .map(|monitor| monitor.name.clone())
+                        .unwrap_or_else(|| entry.monitor.clone());
+                    players.push(WallpaperEntry::new(output, source.clone()));
+                }
+                WallpaperEntry {
+                    path: renderable_path(source),
+                    ..entry
+                }
+            })
+            .collect();
+        (wallpapers, players)
+    });
+    queue_apply(render, move |rendered| {
+        let Ok((wallpapers, players)) = rendered else {
+            eprintln!("Could not prepare the wallpapers to apply");
+            return;
+        };
+
+        set_wallpapers(wallpapers.clone());
+        set_preloads_from_wallpapers();
+        save_config();
+
+        show_wallpapers(wallpapers.clone());
+        mpvpaper::play_all(players);
+
+        send_post_apply(wallpapers.into_iter().map(|entry| (entry.monitor, entry.path)).collect(), done);
+    });
+    post_apply
+}
+
+// Sets several monitors in one go, monitors left out keep what they show.
+pub fn assign_wallpapers(assignments: Vec<(String, String)>) -> PostApply {
+    let monitors = get_monitor_details();
+    let is_assigned = |identifier: &str| {
+        monitors
+            .iter()
+            .any(|monitor| monitor.matches(identifier) && assignments.iter().any(|(name, _)| *name == monitor.name))
+    };
+
+    let mut wallpapers: Vec<WallpaperEntry> = if assignments.len() < monitors.len() {
+        get_wallpapers()
+            .into_iter()
+            .filter(|entry| !is_assigned(&entry.monitor))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    wallpapers.extend(
+        assignments
+            .iter()
+            .map(|(monitor, path)| WallpaperEntry::new(get_monitor_identifier(monitor), path.clone()).with_mode(get_settings().fit_mode)),
+    );
+    apply_wallpapers(wallpapers)
+}
+
+pub fn preview_wallpaper(path: String) {
+    let monitor = get_selected_monitor();
+    let request = PREVIEW_REQUEST.get() + 1;
+    PREVIEW_REQUEST.set(request);
+
+    glib::spawn_future_local(async move {
+        let shown_path = render_in_background(path.clone()).await;
+        if PREVIEW_REQUEST.get() != request {
+            return;
+        }
+
+        hyprpaper("preload", &shown_path);
+        if monitor.is_empty() {
+            for monitor in get_state_monitors() {
+                hyprpaper("wallpaper", &format!("{},{}", monitor, shown_path));
+            }
+        } else {
+            hyprpaper("wallpaper", &format!("{},{}", monitor, shown_path));
+        }
+        hyprpaper("unload", "unused");
+
+        set_preview(Some(WallpaperEntry::new(monitor, path)));
+    });
+}
+
+async fn render_in_background(path: String) -> String {
+    let source = path.clone();
+    gio::spawn_blocking(move || renderable_path(source)).await.unwrap_or(path)
+}
+
+// Formats hyprpaper can't decode are converted first and animations are shown as their first frame,
+// on failure hyprpaper gets the original.
+fn renderable_path(path: String) -> String {
+    let result = if is_animated(&path) {
+        mpvpaper::fallback_frame(&path)
+    } else {
+        backend_path(&path)
+    };
+    result.unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        path
+    })
+}
+
+pub fn apply_preview() {
+    if let Some(preview) = get_preview() {
+        set_wallpaper(preview.monitor, preview.path);
     }
-    let _ = Command::new("sh")
-        .arg("-c")
-        .arg("hyprctl hyprpaper unload unused")
+}
+
+pub fn cancel_preview() {
+    PREVIEW_REQUEST.set(PREVIEW_REQUEST.get() + 1);
+    if get_preview().is_none() {
+        return;
+    }
+    set_preview(None);
+
+    show_wallpapers(get_wallpapers());
+}
+
+fn show_wallpapers(mut wallpapers: Vec<WallpaperEntry>) {
+    // Entries without a monitor go first so per-monitor entries override them.
+    wallpapers.sort_by_key(|entry| !entry.monitor.is_empty());
+    for entry in wallpapers {
+        hyprpaper("preload", &entry.path);
+        hyprpaper("wallpaper", &entry.value());
+    }
+    hyprpaper("unload", "unused");
+}
+
+fn hyprpaper(command: &str, argument: &str) {
+    let _ = Command::new("hyprctl")
+        .arg("hyprpaper")
+        .arg(command)
+        .arg(argument)
         .output();
 }
 
-pub fn get_monitors() -> Vec<String> {
-    let mut result = Vec::new();
+// What hyprpaper shows right now, changes other tools made over its IPC included. None when
+// hyprpaper couldn't be asked.
+pub fn list_active() -> Option<Vec<WallpaperEntry>> {
+    let output = Command::new("hyprctl")
+        .arg("hyprpaper")
+        .arg("listactive")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
 
-    let output = Command::new("sh")
-        .arg("-c")
-        .arg("hyprctl monitors | awk '/Monitor/ {print $2}'")
+    let active: Vec<WallpaperEntry> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(monitor, path)| WallpaperEntry::new(monitor.trim().to_string(), path.trim().to_string()))
+        .collect();
+    (!active.is_empty()).then_some(active)
+}
+
+// The size is as the monitor is seen, rotated monitors have their width and height swapped.
+#[derive(Clone, PartialEq)]
+pub struct Monitor {
+    pub name: String,
+    pub description: String,
+    pub width: i32,
+    pub height: i32,
+    pub scale: f64,
+    pub transform: i32,
+}
+
+impl Monitor {
+    // hyprpaper and Hyprland match `desc:` identifiers against the start of the description.
+    pub fn matches(&self, identifier: &str) -> bool {
+        match identifier.strip_prefix("desc:") {
+            Some(description) => !description.trim().is_empty() && self.description.starts_with(description.trim()),
+            None => identifier == self.name,
+        }
+    }
+
+    pub fn description_identifier(&self) -> String {
+        format!("desc:{}", self.description)
+    }
+
+    pub fn describe_size(&self) -> String {
+        let mut description = format!("{}×{}", self.width, self.height);
+        if (self.scale - 1.0).abs() >= 0.01 {
+            description.push_str(&format!(" at {}× scale", self.scale));
+        }
+        if self.transform % 2 == 1 {
+            description.push_str(", rotated");
+        }
+        description
+    }
+}
+
+pub fn get_monitors() -> Vec<Monitor> {
+    try_get_monitors().unwrap_or_else(|err| panic!("{}", err))
+}
+
+pub fn try_get_monitors() -> Result<Vec<Monitor>, String> {
+    let output = Command::new("hyprctl")
+        .arg("monitors")
         .output()
-        .unwrap_or_else(|err| panic!("Could not get monitors: {}", err));
+        .map_err(|err| format!("Could not get monitors: {}", err))?;
 
     if !output.status.success() {
-        panic!("Command failed with status: {}", output.status);
+        return Err(format!("Command failed with status: {}", output.status));
     }
 
-    let monitors_str = String::from_utf8_lossy(&output.stdout);
-
-    for monitor in monitors_str.lines() {
-        let trimmed = monitor.trim();
-        if !trimmed.is_empty() {
-            result.push(trimmed.to_string());
+    let mut result: Vec<Monitor> = Vec::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let trimmed = line.trim();
+        if let Some(rest) = trimmed.strip_prefix("Monitor ") {
+            if let Some(name) = rest.split_whitespace().next() {
+                result.push(Monitor {
+                    name: name.to_string(),
+                    description: String::new(),
+                    width: 0,
+                    height: 0,
+                    scale: 1.0,
+                    transform: 0,
+                });
+            }
+        } else if let Some(monitor) = result.last_mut()
+            && monitor.width == 0
+            && let Some((width, height)) = parse_mode(trimmed)
+        {
+            monitor.width = width;
+            monitor.height = height;
+        } else if let Some(description) = trimmed.strip_prefix("description:")
+            && let Some(monitor) = result.last_mut()
+        {
+            monitor.description = description.trim().to_string();
+        } else if let Some(scale) = trimmed.strip_prefix("scale:")
+            && let Some(monitor) = result.last_mut()
+            && let Ok(scale) = scale.trim().parse()
+        {
+            monitor.scale = scale;
+        } else if let Some(transform) = trimmed.strip_prefix("transform:")
+            && let Some(monitor) = result.last_mut()
+            && let Ok(transform) = transform.trim().parse::<i32>()
+        {
+            // Odd transforms turn the output by 90 or 270 degrees, the mode line still has the unrotated size.
+            monitor.transform = transform;
+            if transform % 2 == 1 {
+                (monitor.width, monitor.height) = (monitor.height, monitor.width);
+            }
         }
     }
 
-    result
+    Ok(result)
+}
+
+// The line after the monitor name reads like `2560x1440@143.97200 at 0x0`.
+fn parse_mode(line: &str) -> Option<(i32, i32)> {
+    let (mode, _) = line.split_once('@')?;
+    let (width, height) = mode.split_once('x')?;
+    Some((width.parse().ok()?, height.parse().ok()?))
 }
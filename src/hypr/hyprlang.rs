@@ -0,0 +1,410 @@
+use std::borrow::Cow;
+use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::io::Error;
+use std::path::{Path, PathBuf};
+
+pub struct SourceFile {
+    pub path: PathBuf,
+    pub lines: Vec<String>,
+    pub ends_with_newline: bool,
+}
+
+pub struct Assignment {
+    pub key: String,
+    pub value: String,
+    pub file: usize,
+    pub line: usize,
+}
+
+#[derive(Clone)]
+pub struct Diagnostic {
+    pub file: PathBuf,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn new(file: &Path, line: Option<usize>, message: String) -> Self {
+        Self {
+            file: file.to_path_buf(),
+            line,
+            message,
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.file.display(), line + 1, self.message),
+            None => write!(f, "{}: {}", self.file.display(), self.message),
+        }
+    }
+}
+
+// A config file together with everything it sources. Variables are already substituted
+// in the assignment values, and every assignment remembers the file and line it came from.
+pub struct Document {
+    pub files: Vec<SourceFile>,
+    pub assignments: Vec<Assignment>,
+    pub diagnostics: Vec<Diagnostic>,
+    variables: Vec<(String, String)>,
+}
+
+impl Document {
+    pub fn read(path: &Path) -> Result<Self, Error> {
+        let mut document = Document {
+            files: Vec::new(),
+            assignments: Vec::new(),
+            diagnostics: Vec::new(),
+            variables: Vec::new(),
+        };
+        let mut stack = Vec::new();
+        document.read_file(path, &mut stack)?;
+        Ok(document)
+    }
+
+    pub fn file_path(&self, file: usize) -> &Path {
+        &self.files[file].path
+    }
+
+    fn read_file(&mut self, path: &Path, stack: &mut Vec<PathBuf>) -> Result<(), Error> {
+        let canonical = fs::canonicalize(path)?;
+        if stack.contains(&canonical) {
+            return Err(Error::other("it sources itself"));
+        }
+        if self.files.iter().any(|file| fs::canonicalize(&file.path).ok().as_ref() == Some(&canonical)) {
+            return Ok(());
+        }
+
+        let content = fs::read_to_string(path)?;
+        let file = self.files.len();
+        self.files.push(SourceFile {
+            path: path.to_path_buf(),
+            lines: content.lines().map(str::to_string).collect(),
+            ends_with_newline: content.ends_with('\n') || content.is_empty(),
+        });
+        stack.push(canonical);
+
+        let mut categories: Vec<String> = Vec::new();
+        for (line_index, line) in content.lines().enumerate() {
+            match parse_line(line) {
+                Line::CategoryStart(name) => categories.push(name.to_string()),
+                Line::CategoryEnd => {
+                    if categories.pop().is_none() {
+                        self.diagnostics
+                            .push(Diagnostic::new(path, Some(line_index), "unexpected '}'".to_string()));
+                    }
+                }
+                Line::Assignment { key, value } => {
+                    let value = self.substitute_variables(&value);
+
+                    if let Some(name) = key.strip_prefix('$') {
+                        self.variables.retain(|(variable, _)| variable != name);
+                        self.variables.push((name.to_string(), value));
+                        continue;
+                    }
+
+                    if key == "source" && categories.is_empty() {
+                        let source = resolve_path(&value, path);
+                        if let Err(err) = self.read_file(&source, stack) {
+                            self.diagnostics.push(Diagnostic::new(
+                                path,
+                                Some(line_index),
+                                format!("could not source {}: {}", source.display(), err),
+                            ));
+                        }
+                        continue;
+                    }
+
+                    let mut full_key = categories.join(":");
+                    if !full_key.is_empty() {
+                        full_key.push(':');
+                    }
+                    full_key.push_str(key);
+
+                    self.assignments.push(Assignment {
+                        key: full_key,
+                        value,
+                        file,
+                        line: line_index,
+                    });
+                }
+                Line::Other => {
+                    if !line[..comment_start(line).unwrap_or(line.len())].trim().is_empty() {
+                        self.diagnostics.push(Diagnostic::new(
+                            path,
+                            Some(line_index),
+                            format!("malformed line '{}'", line.trim()),
+                        ));
+                    }
+                }
+            }
+        }
+
+        if let Some(category) = categories.last() {
+            self.diagnostics
+                .push(Diagnostic::new(path, None, format!("'{}' is never closed", category)));
+        }
+
+        stack.pop();
+        Ok(())
+    }
+
+    // Unknown names fall back to environment variables and are left untouched otherwise.
+    fn substitute_variables(&self, value: &str) -> String {
+        let mut result = String::new();
+        let mut rest = value;
+
+        while let Some(dollar) = rest.find('$') {
+            result.push_str(&rest[..dollar]);
+            let after = &rest[dollar + 1..];
+            let name_length = after
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(after.len());
+            let name = &after[..name_length];
+
+            let replacement = self
+                .variables
+                .iter()
+                .find(|(variable, _)| variable == name)
+                .map(|(_, value)| value.clone())
+                .or_else(|| env::var(name).ok().filter(|_| !name.is_empty()));
+            match replacement {
+                Some(replacement) => result.push_str(&replacement),
+                None => {
+                    result.push('$');
+                    result.push_str(name);
+                }
+            }
+            rest = &after[name_length..];
+        }
+
+        result.push_str(rest);
+        result
+    }
+
+    // The reverse of substitution for paths, so a written path keeps using `$wpdir` when it can.
+    pub fn with_variables(&self, path: &str) -> String {
+        self.variables
+            .iter()
+            .filter_map(|(name, value)| {
+                let value = expand_home(value);
+                let rest = path.strip_prefix(value.as_str())?;
+                (value.starts_with('/') && value.len() > 1 && (rest.is_empty() || rest.starts_with('/')))
+                    .then(|| (value.len(), format!("${}{}", name, rest)))
+            })
+            .max_by_key(|(length, _)| *length)
+            .map(|(_, path)| path)
+            .unwrap_or_else(|| path.to_string())
+    }
+}
+
+pub fn expand_home(path: &str) -> String {
+    match (path.strip_prefix('~'), env::home_dir()) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            format!("{}{}", home.to_string_lossy(), rest)
+        }
+        _ => path.to_string(),
+    }
+}
+
+fn resolve_path(path: &str, relative_to: &Path) -> PathBuf {
+    let path = PathBuf::from(expand_home(path));
+    if path.is_absolute() {
+        return path;
+    }
+    relative_to
+        .parent()
+        .map(|parent| parent.join(&path))
+        .unwrap_or(path)
+}
+
+pub enum Line<'a> {
+    Assignment { key: &'a str, value: Cow<'a, str> },
+    CategoryStart(&'a str),
+    CategoryEnd,
+    Other,
+}
+
+pub fn parse_line(line: &str) -> Line<'_> {
+    let line = line[..comment_start(line).unwrap_or(line.len())].trim();
+
+    if line.is_empty() {
+        return Line::Other;
+    }
+    if line == "}" {
+        return Line::CategoryEnd;
+    }
+    if let Some(name) = line.strip_suffix('{') {
+        return Line::CategoryStart(name.trim());
+    }
+
+    // Split on first '=' only
+    match line.split_once('=') {
+        Some((key, value)) => Line::Assignment {
+            key: key.trim(),
+            value: unescape(value.trim()),
+        },
+        None => Line::Other,
+    }
+}
+
+// "##" escapes a literal '#', anything after a single '#' is a comment.
+pub fn comment_start(line: &str) -> Option<usize> {
+    let bytes = line.as_bytes();
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'#' {
+            if bytes.get(index + 1) == Some(&b'#') {
+                index += 2;
+                continue;
+            }
+            return Some(index);
+        }
+        index += 1;
+    }
+    None
+}
+
+fn unescape(value: &str) -> Cow<'_, str> {
+    if value.contains("##") {
+        Cow::Owned(value.replace("##", "#"))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+pub fn escape(value: &str) -> Cow<'_, str> {
+    if value.contains('#') {
+        Cow::Owned(value.replace('#', "##"))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+pub fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Some(true),
+        "false" | "no" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+pub fn default_config_path(file_name: &str) -> PathBuf {
+    match env::var("XDG_CONFIG_HOME") {
+        Ok(val) => {
+            let mut path = PathBuf::from(val);
+            path.push("hypr");
+            path.push(file_name);
+            path
+        }
+        Err(_) => {
+            let mut path = env::home_dir().unwrap();
+            path.push(".config/hypr");
+            path.push(file_name);
+            path
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::paths::test_dir;
+
+    fn values(document: &Document) -> Vec<(&str, &str)> {
+        document
+            .assignments
+            .iter()
+            .map(|assignment| (assignment.key.as_str(), assignment.value.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn substitutes_variables() {
+        let dir = test_dir("hyprlang-substitute");
+        let path = dir.join("hyprpaper.conf");
+        fs::write(
+            &path,
+            "$wpdir = /walls\n$image = $wpdir/a.png\npreload = $image\nwallpaper = DP-1,$wpdir/b.png\nsplash = $undefined_name\n",
+        )
+        .unwrap();
+
+        let document = Document::read(&path).unwrap();
+        assert_eq!(
+            values(&document),
+            [
+                ("preload", "/walls/a.png"),
+                ("wallpaper", "DP-1,/walls/b.png"),
+                ("splash", "$undefined_name"),
+            ]
+        );
+        assert!(document.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn substitutes_variables_back_into_paths() {
+        let dir = test_dir("hyprlang-with-variables");
+        let path = dir.join("hyprpaper.conf");
+        fs::write(&path, "$walls = /walls\n$nature = /walls/nature\n$relative = walls\n").unwrap();
+
+        let document = Document::read(&path).unwrap();
+        assert_eq!(document.with_variables("/walls/a.png"), "$walls/a.png");
+        assert_eq!(document.with_variables("/walls/nature/a.png"), "$nature/a.png");
+        assert_eq!(document.with_variables("/wallsides/a.png"), "/wallsides/a.png");
+        assert_eq!(document.with_variables("/other/walls/a.png"), "/other/walls/a.png");
+    }
+
+    #[test]
+    fn reads_sourced_files() {
+        let dir = test_dir("hyprlang-source");
+        let path = dir.join("hyprpaper.conf");
+        fs::write(&path, "$wpdir = /walls\nsource = ./wallpapers.conf\nsplash = false\n").unwrap();
+        fs::write(dir.join("wallpapers.conf"), "# Wallpapers\nwallpaper = DP-1,$wpdir/a.png\n").unwrap();
+
+        let document = Document::read(&path).unwrap();
+        assert_eq!(document.files.len(), 2);
+        assert_eq!(values(&document), [("wallpaper", "DP-1,/walls/a.png"), ("splash", "false")]);
+        let wallpaper = &document.assignments[0];
+        assert_eq!((wallpaper.file, wallpaper.line), (1, 1));
+        assert!(document.file_path(wallpaper.file).ends_with("wallpapers.conf"));
+    }
+
+    #[test]
+    fn reports_broken_sources() {
+        let dir = test_dir("hyprlang-broken-source");
+        let path = dir.join("hyprpaper.conf");
+        fs::write(&path, "source = ./missing.conf\nsource = ./loop.conf\n").unwrap();
+        fs::write(dir.join("loop.conf"), "source = ./hyprpaper.conf\n").unwrap();
+
+        let document = Document::read(&path).unwrap();
+        let lines: Vec<Option<usize>> = document.diagnostics.iter().map(|diagnostic| diagnostic.line).collect();
+        assert_eq!(lines, [Some(0), Some(0)]);
+        assert!(document.diagnostics[1].message.contains("sources itself"));
+    }
+
+    #[test]
+    fn expands_home() {
+        let home = env::home_dir().unwrap().to_string_lossy().to_string();
+        assert_eq!(expand_home("~"), home);
+        assert_eq!(expand_home("~/walls/a.png"), format!("{}/walls/a.png", home));
+        assert_eq!(expand_home("~user/a.png"), "~user/a.png");
+        assert_eq!(expand_home("/walls/~/a.png"), "/walls/~/a.png");
+    }
+
+    #[test]
+    fn parses_comments_and_escapes() {
+        assert_eq!(comment_start("wallpaper = ,/a.png # main"), Some(20));
+        assert_eq!(comment_start("path = /a##b.png"), None);
+        assert!(matches!(
+            parse_line("  path = /a##b.png # comment"),
+            Line::Assignment { key: "path", value } if value == "/a#b.png"
+        ));
+        assert!(matches!(parse_line("# only a comment"), Line::Other));
+        assert!(matches!(parse_line("background {"), Line::CategoryStart("background")));
+        assert_eq!(escape("/a#b.png"), "/a##b.png");
+    }
+}
@@ -0,0 +1,241 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::hypr::hyprctl::Monitor;
+use crate::hypr::hyprlang::{Line, comment_start, default_config_path, escape, parse_line};
+use crate::paths::write_atomically;
+
+struct BackgroundBlock {
+    start: usize,
+    end: usize,
+    monitor: String,
+    monitor_line: Option<usize>,
+    path_line: Option<usize>,
+}
+
+pub fn get_default_config_path() -> PathBuf {
+    default_config_path("hyprlock.conf")
+}
+
+// Rewrites only the `path` values of the matching `background` blocks, everything else is kept as is.
+// A monitor without its own block gets a copy of the shared block, so the other monitors keep their image.
+// Only the post-apply worker calls this, so two applies never read and write the file at the same time.
+pub fn sync_background(config_path: &Path, monitors: &[Monitor], monitor: &str, path: &str) {
+    let content = match fs::read_to_string(config_path) {
+        Ok(content) => content,
+        Err(err) => {
+            eprintln!("Could not read {}: {}", config_path.display(), err);
+            return;
+        }
+    };
+
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let blocks = find_background_blocks(&lines);
+    if blocks.is_empty() {
+        return;
+    }
+
+    let targets: Vec<&BackgroundBlock> = blocks
+        .iter()
+        .filter(|block| monitor.is_empty() || is_same_monitor(monitors, &block.monitor, monitor))
+        .collect();
+
+    if !targets.is_empty() {
+        // From the last block to the first, so an inserted line never moves a block still to be edited.
+        for block in targets.into_iter().rev() {
+            set_block_path(&mut lines, block, path);
+        }
+    } else if let Some(shared) = blocks.iter().find(|block| block.monitor.is_empty()) {
+        let mut copy: Vec<String> = lines[shared.start..=shared.end].to_vec();
+        let indent = block_indent(&lines, shared);
+        match shared.monitor_line {
+            Some(monitor_line) => copy[monitor_line - shared.start] = with_value(&copy[monitor_line - shared.start], monitor),
+            None => copy.insert(1, format!("{}monitor = {}", indent, escape(monitor))),
+        }
+        match copy.iter().position(|line| matches!(parse_line(line), Line::Assignment { key: "path", .. })) {
+            Some(path_line) => copy[path_line] = with_value(&copy[path_line], path),
+            None => copy.insert(copy.len() - 1, format!("{}path = {}", indent, escape(path))),
+        }
+        copy.insert(0, String::new());
+        let insert_at = shared.end + 1;
+        lines.splice(insert_at..insert_at, copy);
+    } else {
+        return;
+    }
+
+    let mut new_content = lines.join("\n");
+    if content.ends_with('\n') {
+        new_content.push('\n');
+    }
+    if new_content != content
+        && let Err(err) = write_atomically(config_path, new_content)
+    {
+        eprintln!("Could not write {}: {}", config_path.display(), err);
+    }
+}
+
+// Blocks and wallpapers may name the same monitor by connector or by `desc:`.
+fn is_same_monitor(monitors: &[Monitor], block_monitor: &str, monitor: &str) -> bool {
+    block_monitor == monitor || monitors.iter().any(|known| known.matches(block_monitor) && known.matches(monitor))
+}
+
+fn find_background_blocks(lines: &[String]) -> Vec<BackgroundBlock> {
+    let mut blocks = Vec::new();
+    let mut depth = 0;
+    let mut current: Option<BackgroundBlock> = None;
+
+    for (index, line) in lines.iter().enumerate() {
+        match parse_line(line) {
+            Line::CategoryStart(name) => {
+                if depth == 0 && name == "background" {
+                    current = Some(BackgroundBlock {
+                        start: index,
+                        end: index,
+                        monitor: String::new(),
+                        monitor_line: None,
+                        path_line: None,
+                    });
+                }
+                depth += 1;
+            }
+            Line::CategoryEnd => {
+                depth -= 1;
+                if depth == 0
+                    && let Some(mut block) = current.take()
+                {
+                    block.end = index;
+                    blocks.push(block);
+                }
+            }
+            Line::Assignment { key, value } if depth == 1 => {
+                if let Some(block) = current.as_mut() {
+                    match key {
+                        "monitor" => {
+                            block.monitor = value.to_string();
+                            block.monitor_line = Some(index);
+                        }
+                        "path" => block.path_line = Some(index),
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    blocks
+}
+
+fn set_block_path(lines: &mut Vec<String>, block: &BackgroundBlock, path: &str) {
+    match block.path_line {
+        Some(path_line) => lines[path_line] = with_value(&lines[path_line], path),
+        None => {
+            let indent = block_indent(lines, block);
+            lines.insert(block.end, format!("{}path = {}", indent, escape(path)));
+        }
+    }
+}
+
+// Keeps the key, its spacing and any trailing comment, only the value changes.
+fn with_value(line: &str, value: &str) -> String {
+    let Some(equals) = line.find('=') else {
+        return line.to_string();
+    };
+    let (code, comment) = line.split_at(comment_start(line).unwrap_or(line.len()));
+    let (before, after) = code.split_at(equals + 1);
+    if after.trim().is_empty() {
+        return format!("{} {}{}", before, escape(value), comment);
+    }
+    let leading = &after[..after.len() - after.trim_start().len()];
+    let trailing = &after.trim_start()[after.trim().len()..];
+    format!("{}{}{}{}{}", before, leading, escape(value), trailing, comment)
+}
+
+fn block_indent(lines: &[String], block: &BackgroundBlock) -> String {
+    lines[block.start + 1..block.end]
+        .iter()
+        .find(|line| !line.trim().is_empty())
+        .map(|line| line[..line.len() - line.trim_start().len()].to_string())
+        .unwrap_or_else(|| "    ".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::paths::test_dir;
+
+    const SHARED: &str = "general {\n    hide_cursor = true\n}\n\nbackground {\n    monitor =\n    path = /walls/a.png # shared\n    blur_passes = 2\n}\n";
+
+    fn monitor(name: &str, description: &str) -> Monitor {
+        Monitor {
+            name: name.to_string(),
+            description: description.to_string(),
+            width: 1920,
+            height: 1080,
+            scale: 1.0,
+            transform: 0,
+        }
+    }
+
+    fn sync(name: &str, content: &str, monitors: &[Monitor], target: &str, path: &str) -> String {
+        let config_path = test_dir(name).join("hyprlock.conf");
+        fs::write(&config_path, content).unwrap();
+        sync_background(&config_path, monitors, target, path);
+        fs::read_to_string(&config_path).unwrap()
+    }
+
+    #[test]
+    fn copies_the_shared_block_for_a_monitor_without_one() {
+        let monitors = [monitor("DP-1", "Dell Inc. U2720Q")];
+        let content = sync("hyprlock-copy", SHARED, &monitors, "DP-1", "/walls/b.png");
+        assert_eq!(
+            content,
+            format!("{}\nbackground {{\n    monitor = DP-1\n    path = /walls/b.png # shared\n    blur_passes = 2\n}}\n", SHARED)
+        );
+
+        // The same monitor named by description finds its block instead of getting another copy.
+        assert_eq!(
+            sync("hyprlock-copy-again", &content, &monitors, "desc:Dell Inc. U2720Q", "/walls/c.png"),
+            content.replace("/walls/b.png", "/walls/c.png")
+        );
+    }
+
+    #[test]
+    fn inserts_a_missing_path() {
+        let content = sync(
+            "hyprlock-insert",
+            "background {\n  monitor = DP-1\n  color = rgb(0, 0, 0)\n}",
+            &[],
+            "DP-1",
+            "/walls/b.png",
+        );
+        assert_eq!(content, "background {\n  monitor = DP-1\n  color = rgb(0, 0, 0)\n  path = /walls/b.png\n}");
+    }
+
+    #[test]
+    fn edits_every_targeted_block() {
+        let content = sync(
+            "hyprlock-all",
+            "background {\n    monitor = DP-1\n}\nbackground {\n    monitor = DP-2\n    path = /walls/a.png\n}\nbackground {\n    monitor = HDMI-A-1\n}\n",
+            &[],
+            "",
+            "/walls/b.png",
+        );
+        assert_eq!(
+            content,
+            "background {\n    monitor = DP-1\n    path = /walls/b.png\n}\nbackground {\n    monitor = DP-2\n    path = /walls/b.png\n}\nbackground {\n    monitor = HDMI-A-1\n    path = /walls/b.png\n}\n"
+        );
+    }
+
+    #[test]
+    fn escapes_paths() {
+        let content = sync("hyprlock-escape", SHARED, &[], "", "/walls/b#1.png");
+        assert_eq!(content, SHARED.replace("/walls/a.png", "/walls/b##1.png"));
+
+        let content = sync("hyprlock-escape-insert", "background {\n    monitor = DP-1\n}\n", &[], "DP-1", "/walls/b#1.png");
+        assert_eq!(content, "background {\n    monitor = DP-1\n    path = /walls/b##1.png\n}\n");
+
+        let content = sync("hyprlock-escape-copy", "background {\n    monitor =\n}\n", &[], "DP-1", "/walls/b#1.png");
+        assert!(content.ends_with("background {\n    monitor = DP-1\n    path = /walls/b##1.png\n}\n"), "{}", content);
+    }
+}
@@ -1,121 +1,457 @@
 use std::fmt::Display;
-use std::path::PathBuf;
-use std::{
-    env, fs,
-    io::{BufRead, BufReader, Error, Write},
-};
+use std::io::Error;
+use std::path::{Path, PathBuf};
 
-#[derive(Clone)]
+use crate::hypr::hyprctl::Monitor;
+use crate::hypr::hyprlang::{Diagnostic, Document, comment_start, default_config_path, escape, expand_home};
+use crate::images::scan::is_supported_image;
+use crate::images::transcode::is_native_to_hyprpaper;
+use crate::paths::write_atomically;
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum FitMode {
+    #[default]
+    Cover,
+    Contain,
+    Tile,
+}
+
+impl FitMode {
+    pub const ALL: [FitMode; 3] = [FitMode::Cover, FitMode::Contain, FitMode::Tile];
+    pub const LABELS: [&str; 3] = ["Cover", "Contain", "Tile"];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FitMode::Cover => "cover",
+            FitMode::Contain => "contain",
+            FitMode::Tile => "tile",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.name() == name)
+    }
+
+    // hyprpaper reads the mode as a prefix of the path.
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            FitMode::Cover => "",
+            FitMode::Contain => "contain:",
+            FitMode::Tile => "tile:",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
 pub struct WallpaperEntry {
     pub monitor: String,
     pub path: String,
+    pub mode: FitMode,
 }
 
 impl WallpaperEntry {
     pub fn new(monitor: String, path: String) -> Self {
         Self {
-            monitor: monitor,
-            path: path,
+            monitor,
+            path,
+            mode: FitMode::default(),
         }
     }
+
+    pub fn with_mode(mut self, mode: FitMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        let (monitor, path) = value.split_once(',')?;
+        let path = path.trim();
+        let (mode, path) = [FitMode::Contain, FitMode::Tile]
+            .into_iter()
+            .find_map(|mode| path.strip_prefix(mode.prefix()).map(|path| (mode, path)))
+            .unwrap_or((FitMode::Cover, path));
+        Some(Self::new(monitor.trim().to_string(), expand_home(path)).with_mode(mode))
+    }
+
+    // The `monitor,path` argument hyprpaper's `wallpaper` takes, in the config and over IPC.
+    pub fn value(&self) -> String {
+        format!("{},{}{}", self.monitor, self.mode.prefix(), self.path)
+    }
 }
 
 pub struct HyprpaperConfig {
     pub preloads: Vec<String>,
     pub wallpapers: Vec<WallpaperEntry>,
 
-    config_path: PathBuf,
+    document: Document,
 }
 
+// Where a line gets inserted: the file index and the line it goes before.
+type Position = (usize, usize);
+
 impl HyprpaperConfig {
     pub fn new(path: PathBuf) -> Result<Self, Error> {
-        let file = fs::File::open(&path)?;
-        let reader = BufReader::new(file);
+        let document = Document::read(&path)?;
 
         let mut cfg = HyprpaperConfig {
             preloads: Vec::new(),
             wallpapers: Vec::new(),
-            config_path: path,
+            document,
         };
 
-        for line in reader.lines() {
-            let line = line?;
-            let line = line.trim();
+        for assignment in &cfg.document.assignments {
+            match assignment.key.as_str() {
+                "preload" if !assignment.value.is_empty() => cfg.preloads.push(expand_home(&assignment.value)),
+                "wallpaper" => {
+                    if let Some(entry) = WallpaperEntry::parse(&assignment.value) {
+                        cfg.wallpapers.push(entry);
+                    }
+                }
+                _ => {}
+            }
+        }
 
-            // Skip empty lines and comments
-            if line.is_empty() || line.starts_with('#') {
-                continue;
+        Ok(cfg)
+    }
+
+    pub fn get_default_config_path() -> PathBuf {
+        default_config_path("hyprpaper.conf")
+    }
+
+    pub fn file_paths(&self) -> Vec<PathBuf> {
+        self.document.files.iter().map(|file| file.path.clone()).collect()
+    }
+
+    // True when both were read from the same files with the same content.
+    pub fn reads_the_same_as(&self, other: &HyprpaperConfig) -> bool {
+        let (files, other_files) = (&self.document.files, &other.document.files);
+        files.len() == other_files.len()
+            && files.iter().zip(other_files).all(|(file, other)| {
+                file.path == other.path && file.lines == other.lines && file.ends_with_newline == other.ends_with_newline
+            })
+    }
+
+    // Checks the config as it is on disk. Monitor names are only checked when they are known.
+    pub fn validate(&self, monitors: Option<&[Monitor]>) -> Vec<Diagnostic> {
+        let document = &self.document;
+        let mut diagnostics = document.diagnostics.clone();
+
+        let mut preloads: Vec<(&Path, usize, String)> = Vec::new();
+        let mut wallpapers: Vec<(&Path, usize, WallpaperEntry)> = Vec::new();
+        for assignment in &document.assignments {
+            let file = document.file_path(assignment.file);
+            match assignment.key.as_str() {
+                "preload" => preloads.push((file, assignment.line, expand_home(&assignment.value))),
+                "wallpaper" => match WallpaperEntry::parse(&assignment.value) {
+                    Some(entry) => wallpapers.push((file, assignment.line, entry)),
+                    None => diagnostics.push(Diagnostic::new(
+                        file,
+                        Some(assignment.line),
+                        format!("wallpaper '{}' should be 'monitor,path'", assignment.value),
+                    )),
+                },
+                _ => {}
             }
+        }
 
-            // Split on first '=' only
-            let parts: Vec<&str> = line.splitn(2, '=').collect();
-            if parts.len() != 2 {
-                continue;
+        for (file, line, path) in &preloads {
+            if !wallpapers.iter().any(|(_, _, entry)| entry.path == *path) {
+                diagnostics.push(Diagnostic::new(file, Some(*line), format!("{} is preloaded but never used", path)));
+            }
+        }
+
+        for (index, (file, line, entry)) in wallpapers.iter().enumerate() {
+            let line = Some(*line);
+            if !Path::new(&entry.path).is_file() {
+                diagnostics.push(Diagnostic::new(file, line, format!("{} does not exist", entry.path)));
+            } else if !is_supported_image(&entry.path) {
+                diagnostics.push(Diagnostic::new(file, line, format!("{} is not a supported image format", entry.path)));
+            } else if !is_native_to_hyprpaper(&entry.path) {
+                diagnostics.push(Diagnostic::new(
+                    file,
+                    line,
+                    format!("hyprpaper can't decode {}, apply it from Ultrapaper to use a converted copy", entry.path),
+                ));
+            }
+            if !preloads.iter().any(|(_, _, path)| *path == entry.path) {
+                diagnostics.push(Diagnostic::new(file, line, format!("{} is not preloaded", entry.path)));
             }
+            if let Some(monitors) = monitors
+                && !entry.monitor.is_empty()
+                && !monitors.iter().any(|monitor| monitor.matches(&entry.monitor))
+            {
+                diagnostics.push(Diagnostic::new(file, line, format!("monitor {} does not exist", entry.monitor)));
+            }
+            let same_monitor = |other: &WallpaperEntry| {
+                other.monitor == entry.monitor
+                    || monitors.is_some_and(|monitors| {
+                        monitors
+                            .iter()
+                            .any(|monitor| monitor.matches(&other.monitor) && monitor.matches(&entry.monitor))
+                    })
+            };
+            if let Some((first_file, first_line, _)) = wallpapers[..index]
+                .iter()
+                .find(|(_, _, other)| same_monitor(other))
+            {
+                let monitor = if entry.monitor.is_empty() { "all monitors" } else { &entry.monitor };
+                diagnostics.push(Diagnostic::new(
+                    file,
+                    line,
+                    format!(
+                        "{} already has a wallpaper at {}:{}",
+                        monitor,
+                        first_file.display(),
+                        first_line + 1
+                    ),
+                ));
+            }
+        }
+
+        diagnostics
+    }
+
+    // Lines whose entry still exists are left untouched, so variables, comments and
+    // other keys survive. Changed entries reuse the lines of the ones they replace,
+    // and new ones go next to the existing entries of the same kind.
+    pub fn save_config(&mut self) {
+        let files = &self.document.files;
+        let mut lines: Vec<Vec<Option<String>>> = files
+            .iter()
+            .map(|file| file.lines.iter().cloned().map(Some).collect())
+            .collect();
+        let mut insertions: Vec<(Position, String)> = Vec::new();
 
-            let key = parts[0].trim();
-            let val = parts[1].trim();
+        let mut new_preloads: Vec<&String> = self.preloads.iter().collect();
+        let mut new_wallpapers: Vec<&WallpaperEntry> = self.wallpapers.iter().collect();
+        let mut free_preload_lines: Vec<Position> = Vec::new();
+        let mut free_wallpaper_lines: Vec<(Position, String)> = Vec::new();
+        let mut last_preload: Option<Position> = None;
+        let mut first_wallpaper: Option<Position> = None;
+        let mut last_wallpaper: Option<Position> = None;
 
-            match key {
+        for assignment in &self.document.assignments {
+            let position = (assignment.file, assignment.line);
+            match assignment.key.as_str() {
                 "preload" => {
-                    if !val.is_empty() {
-                        cfg.preloads.push(val.to_string());
+                    if last_preload.is_none_or(|(file, _)| file == assignment.file) {
+                        last_preload = Some(position);
+                    }
+                    let preload = expand_home(&assignment.value);
+                    match new_preloads.iter().position(|new_preload| **new_preload == preload) {
+                        Some(index) => {
+                            new_preloads.remove(index);
+                        }
+                        None => free_preload_lines.push(position),
                     }
                 }
                 "wallpaper" => {
-                    let wp: Vec<&str> = val.splitn(2, ',').collect();
-                    if wp.len() == 2 {
-                        cfg.wallpapers.push(WallpaperEntry {
-                            monitor: wp[0].trim().to_string(),
-                            path: wp[1].trim().to_string(),
-                        });
+                    if last_wallpaper.is_none_or(|(file, _)| file == assignment.file) {
+                        first_wallpaper.get_or_insert(position);
+                        last_wallpaper = Some(position);
+                    }
+                    let Some(entry) = WallpaperEntry::parse(&assignment.value) else {
+                        continue;
+                    };
+                    match new_wallpapers.iter().position(|new_entry| **new_entry == entry) {
+                        Some(index) => {
+                            new_wallpapers.remove(index);
+                        }
+                        None => free_wallpaper_lines.push((position, entry.monitor)),
                     }
                 }
                 _ => {}
             }
         }
 
-        Ok(cfg)
-    }
+        for (file, line) in free_preload_lines.iter().chain(free_wallpaper_lines.iter().map(|(position, _)| position)) {
+            lines[*file][*line] = None;
+        }
 
-    pub fn get_default_config_path() -> PathBuf {
-        let config_path = match env::var("XDG_CONFIG_HOME") {
-            Ok(val) => {
-                let mut path = PathBuf::from(val);
-                path.push("hypr/hyprpaper.conf");
-                path
+        let end_of_main_file = (0, files[0].lines.len());
+        let preload_anchor = last_preload
+            .map(|(file, line)| (file, line + 1))
+            .or(first_wallpaper)
+            .unwrap_or(end_of_main_file);
+        let wallpaper_anchor = last_wallpaper
+            .map(|(file, line)| (file, line + 1))
+            .unwrap_or(end_of_main_file);
+
+        for preload in new_preloads {
+            let line = format!("preload = {}", escape(&self.document.with_variables(preload)));
+            if free_preload_lines.is_empty() {
+                insertions.push((preload_anchor, line));
+            } else {
+                let (file, index) = free_preload_lines.remove(0);
+                lines[file][index] = Some(reuse_line(&files[file].lines[index], &line));
             }
-            Err(_) => {
-                let mut path = env::home_dir().unwrap();
-                path.push(".config/hypr/hyprpaper.conf");
-                path
+        }
+
+        for entry in new_wallpapers {
+            let line = format!(
+                "wallpaper = {},{}{}",
+                entry.monitor,
+                entry.mode.prefix(),
+                escape(&self.document.with_variables(&entry.path))
+            );
+            let free_line = free_wallpaper_lines
+                .iter()
+                .position(|(_, monitor)| *monitor == entry.monitor)
+                .or((!free_wallpaper_lines.is_empty()).then_some(0));
+            match free_line {
+                Some(index) => {
+                    let ((file, line_index), _) = free_wallpaper_lines.remove(index);
+                    lines[file][line_index] = Some(reuse_line(&files[file].lines[line_index], &line));
+                }
+                None => insertions.push((wallpaper_anchor, line)),
             }
-        };
+        }
 
-        config_path
-    }
+        for (index, file) in files.iter().enumerate() {
+            let mut output: Vec<String> = Vec::new();
+            for line_index in 0..=lines[index].len() {
+                for ((file_index, before), line) in &insertions {
+                    if *file_index == index && *before == line_index {
+                        output.push(line.clone());
+                    }
+                }
+                if let Some(Some(line)) = lines[index].get(line_index) {
+                    output.push(line.clone());
+                }
+            }
 
-    pub fn save_config(&self) {
-        let mut file = fs::OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .open(&self.config_path)
-            .unwrap();
-        match file.write_all(self.to_string().as_bytes()) {
-            Ok(()) => {}
-            Err(_error) => {}
-        };
+            if output == file.lines {
+                continue;
+            }
+
+            let mut content = output.join("\n");
+            if file.ends_with_newline && !content.is_empty() {
+                content.push('\n');
+            }
+            if let Err(err) = write_atomically(&file.path, content) {
+                eprintln!("Could not write {}: {}", file.path.display(), err);
+            }
+        }
+
+        let main_path = files[0].path.clone();
+        match Document::read(&main_path) {
+            Ok(document) => self.document = document,
+            Err(err) => eprintln!("Could not reload {}: {}", main_path.display(), err),
+        }
     }
 }
 
+// A reused line keeps its indentation and trailing comment.
+fn reuse_line(old: &str, line: &str) -> String {
+    let indent = &old[..old.len() - old.trim_start().len()];
+    let comment = comment_start(old).map(|start| &old[old[..start].trim_end().len()..]).unwrap_or("");
+    format!("{}{}{}", indent, line, comment)
+}
+
 impl Display for HyprpaperConfig {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for preload in &self.preloads {
-            writeln!(f, "preload = {}", preload)?;
+            writeln!(f, "preload = {}", escape(preload))?;
         }
         for wallpaper in &self.wallpapers {
-            writeln!(f, "wallpaper = {},{}", wallpaper.monitor, wallpaper.path)?;
+            writeln!(f, "wallpaper = {},{}{}", wallpaper.monitor, wallpaper.mode.prefix(), escape(&wallpaper.path))?;
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use super::*;
+    use crate::paths::test_dir;
+
+    fn entry(monitor: &str, path: &str) -> WallpaperEntry {
+        WallpaperEntry::new(monitor.to_string(), path.to_string())
+    }
+
+    #[test]
+    fn keeps_comments_blank_lines_and_variables() {
+        let dir = test_dir("hyprpaper-round-trip");
+        let path = dir.join("hyprpaper.conf");
+        let content = "# Wallpapers\n$wpdir = /walls\n\npreload = $wpdir/a.png\n# the main screen\nwallpaper = DP-1,$wpdir/a.png # left\n\nsplash = false\n";
+        fs::write(&path, content).unwrap();
+
+        let mut config = HyprpaperConfig::new(path.clone()).unwrap();
+        assert_eq!(config.preloads, ["/walls/a.png"]);
+        assert!(config.wallpapers == [entry("DP-1", "/walls/a.png")]);
+
+        config.save_config();
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+
+        config.preloads = vec!["/walls/b.png".to_string()];
+        config.wallpapers = vec![entry("DP-1", "/walls/b.png")];
+        config.save_config();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# Wallpapers\n$wpdir = /walls\n\npreload = $wpdir/b.png\n# the main screen\nwallpaper = DP-1,$wpdir/b.png # left\n\nsplash = false\n"
+        );
+    }
+
+    #[test]
+    fn adds_entries_next_to_existing_ones() {
+        let dir = test_dir("hyprpaper-insert");
+        let path = dir.join("hyprpaper.conf");
+        fs::write(&path, "preload = /walls/a.png\nwallpaper = DP-1,/walls/a.png\nsplash = false").unwrap();
+
+        let mut config = HyprpaperConfig::new(path.clone()).unwrap();
+        config.preloads.push("/walls/b#1.png".to_string());
+        config.wallpapers.push(entry("HDMI-A-1", "/walls/b#1.png").with_mode(FitMode::Contain));
+        config.save_config();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "preload = /walls/a.png\npreload = /walls/b##1.png\nwallpaper = DP-1,/walls/a.png\nwallpaper = HDMI-A-1,contain:/walls/b##1.png\nsplash = false"
+        );
+        let config = HyprpaperConfig::new(path).unwrap();
+        assert_eq!(config.wallpapers[1].path, "/walls/b#1.png");
+        assert!(config.wallpapers[1].mode == FitMode::Contain);
+    }
+
+    #[test]
+    fn writes_entries_back_to_the_sourced_file() {
+        let dir = test_dir("hyprpaper-source");
+        let path = dir.join("hyprpaper.conf");
+        let main_content = "$wpdir = /walls\nsource = ./wallpapers.conf\nsplash = false\n";
+        fs::write(&path, main_content).unwrap();
+        fs::write(
+            dir.join("wallpapers.conf"),
+            "preload = $wpdir/a.png\nwallpaper = DP-1,$wpdir/a.png\nwallpaper = HDMI-A-1,$wpdir/a.png\n",
+        )
+        .unwrap();
+
+        let mut config = HyprpaperConfig::new(path.clone()).unwrap();
+        config.preloads.push("/walls/b.png".to_string());
+        config.wallpapers = vec![entry("DP-1", "/walls/a.png"), entry("HDMI-A-1", "/walls/b.png")];
+        config.save_config();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), main_content);
+        assert_eq!(
+            fs::read_to_string(dir.join("wallpapers.conf")).unwrap(),
+            "preload = $wpdir/a.png\npreload = $wpdir/b.png\nwallpaper = DP-1,$wpdir/a.png\nwallpaper = HDMI-A-1,$wpdir/b.png\n"
+        );
+    }
+
+    #[test]
+    fn expands_home_in_paths() {
+        let dir = test_dir("hyprpaper-home");
+        let path = dir.join("hyprpaper.conf");
+        let content = "preload = ~/walls/a.png\nwallpaper = ,~/walls/a.png\n";
+        fs::write(&path, content).unwrap();
+
+        let home = env::home_dir().unwrap().to_string_lossy().to_string();
+        let mut config = HyprpaperConfig::new(path.clone()).unwrap();
+        assert_eq!(config.preloads, [format!("{}/walls/a.png", home)]);
+        assert_eq!(config.wallpapers[0].path, format!("{}/walls/a.png", home));
+
+        // Unchanged entries match their expanded form and keep the `~`.
+        config.save_config();
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+    }
+}
@@ -1,2 +1,6 @@
-pub mod hyprpaper;
+pub mod events;
 pub mod hyprctl;
+pub mod hyprlang;
+pub mod hyprlock;
+pub mod hyprpaper;
+pub mod mpvpaper;
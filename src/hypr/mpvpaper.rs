@@ -0,0 +1,203 @@
+use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::Write;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Stdio};
+use std::thread;
+
+use gtk::gdk_pixbuf::Pixbuf;
+
+use crate::hypr::hyprpaper::WallpaperEntry;
+use crate::images::scan::is_video;
+use crate::paths::{animated_wallpapers_file, config_dir, runtime_dir, video_frames_dir};
+use crate::settings::get_settings;
+use crate::state::get_monitors;
+
+// mpvpaper's name for every output, used for entries without a monitor.
+const ALL_OUTPUTS: &str = "ALL";
+
+fn socket_path(monitor: &str) -> PathBuf {
+    let name = if monitor.is_empty() { "all" } else { monitor };
+    runtime_dir().join(format!("mpv-{}.sock", name))
+}
+
+pub fn frame_path(path: &str) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    let stem = Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    video_frames_dir().join(format!("{}-{:016x}.png", stem, hasher.finish()))
+}
+
+// hyprpaper.conf keeps this still frame, so the output isn't empty while no player runs.
+pub fn fallback_frame(path: &str) -> Result<String, String> {
+    let destination = frame_path(path);
+    let modified = |path: &Path| fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+    if modified(&destination).is_some_and(|frame| modified(Path::new(path)).is_some_and(|original| frame >= original)) {
+        return Ok(destination.to_string_lossy().to_string());
+    }
+
+    fs::create_dir_all(video_frames_dir())
+        .map_err(|err| format!("Could not create {}: {}", video_frames_dir().display(), err))?;
+
+    if is_video(path) {
+        // mpv names the frame 00000001.png, a private directory keeps it from clashing.
+        let output_dir = video_frames_dir().join(format!(".{}", process::id()));
+        let result = Command::new("mpv")
+            .args(["--no-config", "--no-audio", "--frames=1", "--vo=image", "--vo-image-format=png"])
+            .arg(format!("--vo-image-outdir={}", output_dir.display()))
+            .arg("--")
+            .arg(path)
+            .output()
+            .map_err(|err| format!("Could not run mpv: {}", err))
+            .and_then(|_| {
+                fs::rename(output_dir.join("00000001.png"), &destination)
+                    .map_err(|err| format!("Could not extract a frame from {}: {}", path, err))
+            });
+        let _ = fs::remove_dir_all(&output_dir);
+        result?;
+    } else {
+        let pixbuf = Pixbuf::from_file(path).map_err(|err| format!("Could not load {}: {}", path, err))?;
+        pixbuf
+            .savev(&destination, "png", &[])
+            .map_err(|err| format!("Could not save {}: {}", destination.display(), err))?;
+    }
+
+    Ok(destination.to_string_lossy().to_string())
+}
+
+fn read_saved() -> Vec<WallpaperEntry> {
+    fs::read_to_string(animated_wallpapers_file())
+        .map(|content| content.lines().filter_map(WallpaperEntry::parse).collect())
+        .unwrap_or_default()
+}
+
+fn write_saved(wallpapers: &[WallpaperEntry]) {
+    let content: String = wallpapers
+        .iter()
+        .map(|entry| format!("{},{}\n", entry.monitor, entry.path))
+        .collect();
+    if let Err(err) = fs::create_dir_all(config_dir()).and_then(|_| fs::write(animated_wallpapers_file(), content)) {
+        eprintln!("Could not save animated wallpapers: {}", err);
+    }
+}
+
+pub fn play(monitor: &str, path: &str) {
+    stop(monitor);
+
+    let mut saved = read_saved();
+    saved.push(WallpaperEntry::new(monitor.to_owned(), path.to_owned()));
+    write_saved(&saved);
+
+    spawn_player(monitor, path);
+}
+
+// Replaces every player, as applying a whole set of wallpapers does.
+pub fn play_all(wallpapers: Vec<WallpaperEntry>) {
+    stop("");
+
+    write_saved(&wallpapers);
+    for entry in &wallpapers {
+        spawn_player(&entry.monitor, &entry.path);
+    }
+}
+
+// A player on every output covers this monitor as well, it is replaced by one player
+// on each of the other monitors.
+pub fn stop(monitor: &str) {
+    let saved = read_saved();
+    if saved.is_empty() {
+        return;
+    }
+
+    if monitor.is_empty() {
+        let _ = Command::new("pkill")
+            .arg("-f")
+            .arg(runtime_dir().join("mpv-").as_os_str())
+            .output();
+        write_saved(&[]);
+        return;
+    }
+
+    let mut kept = Vec::new();
+    for entry in &saved {
+        if entry.monitor == monitor {
+            kill_player(monitor);
+        } else if entry.monitor.is_empty() {
+            kill_player("");
+            for other in get_monitors() {
+                if other != monitor && !saved.iter().any(|entry| entry.monitor == other) {
+                    spawn_player(&other, &entry.path);
+                    kept.push(WallpaperEntry::new(other, entry.path.clone()));
+                }
+            }
+        } else {
+            kept.push(entry.clone());
+        }
+    }
+
+    write_saved(&kept);
+}
+
+// hyprpaper.conf only knows the still frame of an animation that plays, this finds the animation.
+pub fn source_path(path: &str) -> Option<String> {
+    read_saved()
+        .into_iter()
+        .find(|entry| frame_path(&entry.path) == Path::new(path))
+        .map(|entry| entry.path)
+}
+
+fn kill_player(monitor: &str) {
+    let _ = Command::new("pkill").arg("-f").arg(socket_path(monitor).as_os_str()).output();
+}
+
+// Players don't outlive the session, `ultrapaper video start` brings the saved ones back.
+pub fn start_saved() {
+    for entry in read_saved() {
+        kill_player(&entry.monitor);
+        spawn_player(&entry.monitor, &entry.path);
+    }
+}
+
+pub fn has_players() -> bool {
+    !read_saved().is_empty()
+}
+
+fn spawn_player(monitor: &str, path: &str) {
+    if let Err(err) = fs::create_dir_all(runtime_dir()) {
+        eprintln!("Could not create {}: {}", runtime_dir().display(), err);
+    }
+
+    let mut command = Command::new("mpvpaper");
+    if get_settings().video_pause_on_fullscreen {
+        command.arg("--auto-pause");
+    }
+    command
+        .arg("-o")
+        .arg(format!("no-audio loop input-ipc-server={}", socket_path(monitor).display()))
+        .arg(if monitor.is_empty() { ALL_OUTPUTS } else { monitor })
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+
+    match command.spawn() {
+        Ok(mut child) => {
+            thread::spawn(move || child.wait());
+        }
+        Err(err) => eprintln!("Could not start mpvpaper: {}", err),
+    }
+}
+
+pub fn set_paused(paused: bool) {
+    let message = format!("{{\"command\":[\"set_property\",\"pause\",{}]}}\n", paused);
+    for entry in read_saved() {
+        let socket = socket_path(&entry.monitor);
+        if let Err(err) = UnixStream::connect(&socket).and_then(|mut stream| stream.write_all(message.as_bytes())) {
+            eprintln!("Could not reach the player at {}: {}", socket.display(), err);
+        }
+    }
+}
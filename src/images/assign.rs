@@ -0,0 +1,54 @@
+use gio::glib;
+
+use crate::hypr::hyprctl::Monitor;
+use crate::images::fit::Fit;
+use crate::images::image_info::ImageInfo;
+use crate::images::scan::is_animated;
+
+// Candidates scoring this close to the best one are picked at random, so assigning again gives a new set.
+const SCORE_TOLERANCE: f64 = 0.05;
+const MAX_CANDIDATES: usize = 8;
+
+// Lower is better: the share of the image lost to cropping plus how much it has to be blown up.
+fn score(fit: &Fit) -> f64 {
+    fit.crop + (fit.scale - 1.0).max(0.0)
+}
+
+// Picks a different image for every monitor, largest monitors choose first.
+pub fn auto_assign(paths: &[String], monitors: &[Monitor]) -> Vec<(String, String)> {
+    let mut images: Vec<(&String, i32, i32)> = paths
+        .iter()
+        .filter(|path| !is_animated(path))
+        .filter_map(|path| ImageInfo::read(path).map(|info| (path, info.width, info.height)))
+        .filter(|(_, width, height)| *width > 0 && *height > 0)
+        .collect();
+
+    let mut monitors: Vec<&Monitor> = monitors.iter().collect();
+    monitors.sort_by_key(|monitor| -(monitor.width as i64 * monitor.height as i64));
+
+    let mut assignments = Vec::new();
+    for monitor in monitors {
+        let mut candidates: Vec<(usize, f64)> = images
+            .iter()
+            .enumerate()
+            .filter_map(|(index, (_, width, height))| {
+                Fit::new(*width, *height, monitor.width, monitor.height).map(|fit| (index, score(&fit)))
+            })
+            .collect();
+        candidates.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        let Some(&(_, best)) = candidates.first() else {
+            break;
+        };
+        let pool = candidates
+            .iter()
+            .take(MAX_CANDIDATES)
+            .take_while(|(_, score)| *score <= best + SCORE_TOLERANCE)
+            .count();
+        let (index, _) = candidates[glib::random_int_range(0, pool as i32) as usize];
+
+        let (path, _, _) = images.remove(index);
+        assignments.push((monitor.name.clone(), path.clone()));
+    }
+    assignments
+}
@@ -0,0 +1,36 @@
+use std::fs;
+use std::path::Path;
+
+use gtk::gdk_pixbuf::Pixbuf;
+
+use crate::paths::managed_wallpapers_dir;
+
+// Crops are kept with the imported wallpapers, the config keeps pointing at them.
+pub fn crop_image(path: &str, x: i32, y: i32, width: i32, height: i32) -> Result<String, String> {
+    let pixbuf = Pixbuf::from_file(path).map_err(|err| format!("Could not load {}: {}", path, err))?;
+    let x = x.clamp(0, pixbuf.width() - 1);
+    let y = y.clamp(0, pixbuf.height() - 1);
+    let width = width.clamp(1, pixbuf.width() - x);
+    let height = height.clamp(1, pixbuf.height() - y);
+
+    let dir = managed_wallpapers_dir();
+    fs::create_dir_all(&dir).map_err(|err| format!("Could not create {}: {}", dir.display(), err))?;
+
+    let stem = Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let destination = (0..)
+        .map(|n| match n {
+            0 => dir.join(format!("{}-crop-{}x{}.png", stem, width, height)),
+            n => dir.join(format!("{}-crop-{}x{}-{}.png", stem, width, height, n)),
+        })
+        .find(|candidate| !candidate.exists())
+        .unwrap();
+
+    pixbuf
+        .new_subpixbuf(x, y, width, height)
+        .savev(&destination, "png", &[])
+        .map_err(|err| format!("Could not save {}: {}", destination.display(), err))?;
+    Ok(destination.to_string_lossy().to_string())
+}
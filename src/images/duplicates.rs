@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use std::fs;
+use std::time::UNIX_EPOCH;
+
+use gtk::gdk_pixbuf::Pixbuf;
+
+use crate::images::scan::is_animated;
+use crate::paths::{cache_dir, image_hashes_file};
+
+// Out of 64 bits, copies re-encoded at another resolution usually differ by only a few.
+pub const DEFAULT_THRESHOLD: u32 = 6;
+
+pub struct Duplicate {
+    pub path: String,
+    pub width: i32,
+    pub height: i32,
+    pub size: u64,
+}
+
+struct CachedHash {
+    size: u64,
+    modified: u64,
+    hash: u64,
+}
+
+// Groups of near-identical images, each sorted so the copy worth keeping comes first.
+pub fn find_duplicates(paths: &[String], threshold: u32) -> Vec<Vec<Duplicate>> {
+    let mut cache = read_cache();
+    let mut hashes: Vec<(String, u64, u64)> = Vec::new();
+
+    for path in paths.iter().filter(|path| !is_animated(path)) {
+        let Ok(metadata) = fs::metadata(path) else {
+            continue;
+        };
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+
+        let hash = match cache.get(path) {
+            Some(cached) if cached.size == metadata.len() && cached.modified == modified => cached.hash,
+            _ => {
+                let Some(hash) = difference_hash(path) else {
+                    continue;
+                };
+                cache.insert(
+                    path.clone(),
+                    CachedHash {
+                        size: metadata.len(),
+                        modified,
+                        hash,
+                    },
+                );
+                hash
+            }
+        };
+        hashes.push((path.clone(), hash, metadata.len()));
+    }
+
+    write_cache(&cache);
+
+    // An image only joins a group it is close to every member of, so a chain of
+    // small differences can't pull unrelated images into one group.
+    let mut members: Vec<Vec<usize>> = Vec::new();
+    for (index, (_, hash, _)) in hashes.iter().enumerate() {
+        let is_close = |other: &usize| (hash ^ hashes[*other].1).count_ones() <= threshold;
+        match members.iter_mut().find(|group| group.iter().all(is_close)) {
+            Some(group) => group.push(index),
+            None => members.push(vec![index]),
+        }
+    }
+
+    let mut groups: Vec<Vec<Duplicate>> = members
+        .into_iter()
+        .filter(|group| group.len() > 1)
+        .map(|group| {
+            group
+                .into_iter()
+                .map(|index| {
+                    let (path, _, size) = &hashes[index];
+                    let (width, height) = Pixbuf::file_info(path)
+                        .map(|(_, width, height)| (width, height))
+                        .unwrap_or_default();
+                    Duplicate {
+                        path: path.clone(),
+                        width,
+                        height,
+                        size: *size,
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    for group in &mut groups {
+        group.sort_by(|a, b| {
+            (b.width as i64 * b.height as i64)
+                .cmp(&(a.width as i64 * a.height as i64))
+                .then(b.size.cmp(&a.size))
+                .then(a.path.cmp(&b.path))
+        });
+    }
+    groups.sort_by(|a, b| a[0].path.cmp(&b[0].path));
+    groups
+}
+
+// dHash: compares neighbouring pixels of a 9x8 grayscale thumbnail, one bit per pair.
+fn difference_hash(path: &str) -> Option<u64> {
+    let pixbuf = match Pixbuf::from_file_at_scale(path, 9, 8, false) {
+        Ok(pixbuf) => pixbuf,
+        Err(err) => {
+            eprintln!("Could not load {} for hashing: {}", path, err);
+            return None;
+        }
+    };
+
+    let bytes = pixbuf.read_pixel_bytes();
+    let n_channels = pixbuf.n_channels() as usize;
+    let rowstride = pixbuf.rowstride() as usize;
+    let gray = |x: usize, y: usize| -> Option<u32> {
+        let offset = y * rowstride + x * n_channels;
+        let pixel = bytes.get(offset..offset + 3)?;
+        Some(pixel[0] as u32 * 299 + pixel[1] as u32 * 587 + pixel[2] as u32 * 114)
+    };
+
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash = (hash << 1) | (gray(x, y)? > gray(x + 1, y)?) as u64;
+        }
+    }
+    Some(hash)
+}
+
+fn read_cache() -> HashMap<String, CachedHash> {
+    let Ok(content) = fs::read_to_string(image_hashes_file()) else {
+        return HashMap::new();
+    };
+
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(4, '\t');
+            let hash = u64::from_str_radix(fields.next()?, 16).ok()?;
+            let size = fields.next()?.parse().ok()?;
+            let modified = fields.next()?.parse().ok()?;
+            let path = fields.next()?.to_string();
+            Some((path, CachedHash { size, modified, hash }))
+        })
+        .collect()
+}
+
+fn write_cache(cache: &HashMap<String, CachedHash>) {
+    let content: String = cache
+        .iter()
+        .map(|(path, cached)| format!("{:016x}\t{}\t{}\t{}\n", cached.hash, cached.size, cached.modified, path))
+        .collect();
+
+    if let Err(err) = fs::create_dir_all(cache_dir()).and_then(|_| fs::write(image_hashes_file(), content)) {
+        eprintln!("Could not save image hashes: {}", err);
+    }
+}
@@ -0,0 +1,335 @@
+use std::fs::File;
+use std::io::Read;
+
+// EXIF sits near the start of the file, the image data is never read.
+const MAX_HEADER_SIZE: u64 = 256 * 1024;
+
+const TAG_MAKE: u16 = 0x010f;
+const TAG_MODEL: u16 = 0x0110;
+const TAG_DATE_TIME: u16 = 0x0132;
+const TAG_EXIF_IFD: u16 = 0x8769;
+const TAG_EXPOSURE_TIME: u16 = 0x829a;
+const TAG_F_NUMBER: u16 = 0x829d;
+const TAG_ISO: u16 = 0x8827;
+const TAG_DATE_TIME_ORIGINAL: u16 = 0x9003;
+const TAG_FOCAL_LENGTH: u16 = 0x920a;
+const TAG_LENS_MODEL: u16 = 0xa434;
+
+#[derive(Default)]
+pub struct Exif {
+    pub camera: Option<String>,
+    pub lens: Option<String>,
+    pub taken: Option<String>,
+    pub exposure: Option<String>,
+}
+
+impl Exif {
+    pub fn read(path: &str) -> Option<Self> {
+        let mut data = Vec::new();
+        File::open(path)
+            .ok()?
+            .take(MAX_HEADER_SIZE)
+            .read_to_end(&mut data)
+            .ok()?;
+        Self::parse(&data)
+    }
+
+    fn parse(data: &[u8]) -> Option<Self> {
+        let tiff = Tiff::new(find_tiff(data)?)?;
+        let ifd0 = tiff.u32(4)? as usize;
+        let exif_ifd = tiff.find(ifd0, TAG_EXIF_IFD).and_then(|entry| tiff.u32(entry + 8)).map(|offset| offset as usize);
+        let exif_string = |tag| exif_ifd.and_then(|ifd| tiff.string(ifd, tag));
+        let exif_rational = |tag| exif_ifd.and_then(|ifd| tiff.rational(ifd, tag));
+
+        let make = tiff.string(ifd0, TAG_MAKE);
+        let camera = match (make, tiff.string(ifd0, TAG_MODEL)) {
+            (Some(make), Some(model)) if !model.starts_with(&make) => Some(format!("{} {}", make, model)),
+            (_, Some(model)) => Some(model),
+            (make, None) => make,
+        };
+
+        let taken = exif_string(TAG_DATE_TIME_ORIGINAL)
+            .or_else(|| tiff.string(ifd0, TAG_DATE_TIME))
+            .map(|date_time| date_time.replacen(':', "-", 2));
+
+        let mut settings = Vec::new();
+        if let Some((numerator, denominator)) = exif_rational(TAG_F_NUMBER) {
+            settings.push(format!("f/{:.1}", numerator as f64 / denominator as f64));
+        }
+        if let Some((numerator, denominator)) = exif_rational(TAG_EXPOSURE_TIME) {
+            if numerator < denominator {
+                settings.push(format!("1/{:.0} s", denominator as f64 / numerator as f64));
+            } else {
+                settings.push(format!("{:.1} s", numerator as f64 / denominator as f64));
+            }
+        }
+        if let Some(iso) = exif_ifd.and_then(|ifd| tiff.find(ifd, TAG_ISO)).and_then(|entry| tiff.u16(entry + 8)) {
+            settings.push(format!("ISO {}", iso));
+        }
+        if let Some((numerator, denominator)) = exif_rational(TAG_FOCAL_LENGTH) {
+            settings.push(format!("{:.0} mm", numerator as f64 / denominator as f64));
+        }
+
+        let exif = Exif {
+            camera,
+            lens: exif_string(TAG_LENS_MODEL),
+            taken,
+            exposure: (!settings.is_empty()).then(|| settings.join("  ")),
+        };
+        (exif.camera.is_some() || exif.lens.is_some() || exif.taken.is_some() || exif.exposure.is_some())
+            .then_some(exif)
+    }
+}
+
+// JPEG, PNG and WebP wrap the same TIFF structure in their own containers.
+fn find_tiff(data: &[u8]) -> Option<&[u8]> {
+    if data.starts_with(&[0xff, 0xd8]) {
+        let mut position = 2;
+        while position + 4 <= data.len() && data[position] == 0xff {
+            let marker = data[position + 1];
+            let length = u16::from_be_bytes([data[position + 2], data[position + 3]]) as usize;
+            if marker == 0xda || marker == 0xd9 {
+                break;
+            }
+            let segment = data.get(position + 4..position + 2 + length)?;
+            if marker == 0xe1 && segment.starts_with(b"Exif\0\0") {
+                return Some(&segment[6..]);
+            }
+            position += 2 + length;
+        }
+        None
+    } else if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        let mut position = 8;
+        while position + 8 <= data.len() {
+            let length = u32::from_be_bytes(data[position..position + 4].try_into().ok()?) as usize;
+            let chunk = data.get(position + 8..position + 8 + length)?;
+            if &data[position + 4..position + 8] == b"eXIf" {
+                return Some(chunk);
+            }
+            position += 12 + length;
+        }
+        None
+    } else if data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WEBP") {
+        let mut position = 12;
+        while position + 8 <= data.len() {
+            let length = u32::from_le_bytes(data[position + 4..position + 8].try_into().ok()?) as usize;
+            let chunk = data.get(position + 8..position + 8 + length)?;
+            if &data[position..position + 4] == b"EXIF" {
+                return Some(chunk.strip_prefix(b"Exif\0\0").unwrap_or(chunk));
+            }
+            position += 8 + length + length % 2;
+        }
+        None
+    } else if data.starts_with(b"II*\0") || data.starts_with(b"MM\0*") {
+        Some(data)
+    } else {
+        None
+    }
+}
+
+struct Tiff<'a> {
+    data: &'a [u8],
+    little_endian: bool,
+}
+
+impl<'a> Tiff<'a> {
+    fn new(data: &'a [u8]) -> Option<Self> {
+        let little_endian = match data.get(0..2)? {
+            b"II" => true,
+            b"MM" => false,
+            _ => return None,
+        };
+        Some(Self { data, little_endian })
+    }
+
+    fn u16(&self, offset: usize) -> Option<u16> {
+        let bytes = self.data.get(offset..offset + 2)?.try_into().ok()?;
+        Some(if self.little_endian {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        })
+    }
+
+    fn u32(&self, offset: usize) -> Option<u32> {
+        let bytes = self.data.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if self.little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+
+    // Returns the offset of the 12 byte entry for the tag.
+    fn find(&self, ifd: usize, tag: u16) -> Option<usize> {
+        let count = self.u16(ifd)? as usize;
+        (0..count)
+            .map(|index| ifd + 2 + index * 12)
+            .find(|&entry| self.u16(entry) == Some(tag))
+    }
+
+    fn string(&self, ifd: usize, tag: u16) -> Option<String> {
+        let entry = self.find(ifd, tag)?;
+        let count = self.u32(entry + 4)? as usize;
+        // Values of up to four bytes are stored in the entry itself.
+        let offset = if count <= 4 { entry + 8 } else { self.u32(entry + 8)? as usize };
+        let bytes = self.data.get(offset..offset + count)?;
+        let value = String::from_utf8_lossy(bytes).trim_end_matches('\0').trim().to_string();
+        (!value.is_empty()).then_some(value)
+    }
+
+    fn rational(&self, ifd: usize, tag: u16) -> Option<(u32, u32)> {
+        let offset = self.u32(self.find(ifd, tag)? + 8)? as usize;
+        let (numerator, denominator) = (self.u32(offset)?, self.u32(offset + 4)?);
+        (numerator > 0 && denominator > 0).then_some((numerator, denominator))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TYPE_ASCII: u16 = 2;
+    const TYPE_SHORT: u16 = 3;
+    const TYPE_LONG: u16 = 4;
+    const TYPE_RATIONAL: u16 = 5;
+
+    struct Writer {
+        data: Vec<u8>,
+        little_endian: bool,
+    }
+
+    impl Writer {
+        fn u16(&self, value: u16) -> [u8; 2] {
+            if self.little_endian { value.to_le_bytes() } else { value.to_be_bytes() }
+        }
+
+        fn u32(&self, value: u32) -> [u8; 4] {
+            if self.little_endian { value.to_le_bytes() } else { value.to_be_bytes() }
+        }
+
+        fn ifd(&mut self, entries: &[(u16, u16, u32, [u8; 4])]) {
+            self.data.extend(self.u16(entries.len() as u16));
+            for (tag, kind, count, value) in entries {
+                self.data.extend(self.u16(*tag));
+                self.data.extend(self.u16(*kind));
+                self.data.extend(self.u32(*count));
+                self.data.extend(value);
+            }
+            self.data.extend(self.u32(0));
+        }
+
+        fn rational(&mut self, numerator: u32, denominator: u32) {
+            self.data.extend(self.u32(numerator));
+            self.data.extend(self.u32(denominator));
+        }
+    }
+
+    // IFD0 at 8 holds the make at 50, the model in the entry and a pointer to the EXIF IFD at 56,
+    // whose values follow it from 122 on.
+    fn tiff(little_endian: bool) -> Vec<u8> {
+        let mut writer = Writer {
+            data: Vec::new(),
+            little_endian,
+        };
+        writer.data.extend(if little_endian { b"II" } else { b"MM" });
+        writer.data.extend(writer.u16(42));
+        writer.data.extend(writer.u32(8));
+
+        writer.ifd(&[
+            (TAG_MAKE, TYPE_ASCII, 6, writer.u32(50)),
+            (TAG_MODEL, TYPE_ASCII, 4, *b"EOS\0"),
+            (TAG_EXIF_IFD, TYPE_LONG, 1, writer.u32(56)),
+        ]);
+        writer.data.extend(b"Canon\0");
+
+        let [iso_first, iso_second] = writer.u16(100);
+        writer.ifd(&[
+            (TAG_EXPOSURE_TIME, TYPE_RATIONAL, 1, writer.u32(122)),
+            (TAG_F_NUMBER, TYPE_RATIONAL, 1, writer.u32(130)),
+            (TAG_ISO, TYPE_SHORT, 1, [iso_first, iso_second, 0, 0]),
+            (TAG_DATE_TIME_ORIGINAL, TYPE_ASCII, 20, writer.u32(146)),
+            (TAG_FOCAL_LENGTH, TYPE_RATIONAL, 1, writer.u32(138)),
+        ]);
+        writer.rational(1, 200);
+        writer.rational(28, 10);
+        writer.rational(50, 1);
+        writer.data.extend(b"2024:05:01 12:30:00\0");
+        writer.data
+    }
+
+    // A JFIF segment comes first, so the APP1 segment has to be found behind it.
+    fn jpeg(tiff: &[u8]) -> Vec<u8> {
+        let mut data = vec![0xff, 0xd8, 0xff, 0xe0, 0x00, 0x10];
+        data.extend(b"JFIF\0\x01\x01\0\0\x01\0\x01\0\0");
+        data.extend([0xff, 0xe1]);
+        data.extend((2 + 6 + tiff.len() as u16).to_be_bytes());
+        data.extend(b"Exif\0\0");
+        data.extend(tiff);
+        data.extend([0xff, 0xda, 0x00, 0x02, 0xff, 0xd9]);
+        data
+    }
+
+    fn assert_fixture(exif: Option<Exif>) {
+        let exif = exif.expect("the fixture has EXIF data");
+        assert_eq!(exif.camera.as_deref(), Some("Canon EOS"));
+        assert_eq!(exif.lens, None);
+        assert_eq!(exif.taken.as_deref(), Some("2024-05-01 12:30:00"));
+        assert_eq!(exif.exposure.as_deref(), Some("f/2.8  1/200 s  ISO 100  50 mm"));
+    }
+
+    #[test]
+    fn reads_little_endian_jpeg() {
+        assert_fixture(Exif::parse(&jpeg(&tiff(true))));
+    }
+
+    #[test]
+    fn reads_big_endian_jpeg() {
+        assert_fixture(Exif::parse(&jpeg(&tiff(false))));
+    }
+
+    #[test]
+    fn reads_bare_tiff() {
+        assert_fixture(Exif::parse(&tiff(true)));
+    }
+
+    #[test]
+    fn reads_strings_stored_in_the_entry() {
+        let mut writer = Writer {
+            data: b"MM\0\x2a\0\0\0\x08".to_vec(),
+            little_endian: false,
+        };
+        writer.ifd(&[(TAG_MODEL, TYPE_ASCII, 3, *b"R5\0\0")]);
+
+        let exif = Exif::parse(&writer.data).expect("the model is read");
+        assert_eq!(exif.camera.as_deref(), Some("R5"));
+        assert_eq!(exif.exposure, None);
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        let data = jpeg(&tiff(true));
+        let end_of_exif = data.len() - 6;
+        for length in 0..end_of_exif {
+            assert!(Exif::parse(&data[..length]).is_none(), "{} bytes", length);
+        }
+        assert!(Exif::parse(&data[..end_of_exif]).is_some());
+    }
+
+    #[test]
+    fn survives_corrupt_input() {
+        for little_endian in [true, false] {
+            let data = jpeg(&tiff(little_endian));
+            for index in 0..data.len() {
+                for value in [0x00, 0x7f, 0xff] {
+                    let mut corrupt = data.clone();
+                    corrupt[index] = value;
+                    let _ = Exif::parse(&corrupt);
+                }
+            }
+        }
+        assert!(Exif::parse(&[0xff, 0xd8, 0xff, 0xe1, 0x00, 0x00]).is_none());
+        assert!(Exif::parse(b"II*\0\xff\xff\xff\xff").is_none());
+        assert!(Exif::parse(b"").is_none());
+    }
+}
@@ -0,0 +1,157 @@
+use std::cmp::Ordering;
+
+use crate::images::wallpaper_item::WallpaperItem;
+
+const ASPECT_RATIO_TOLERANCE: f64 = 0.05;
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum Orientation {
+    #[default]
+    Any,
+    Landscape,
+    Portrait,
+    Square,
+}
+
+impl Orientation {
+    pub const LABELS: [&str; 4] = ["Any orientation", "Landscape", "Portrait", "Square"];
+    const ALL: [Self; 4] = [Self::Any, Self::Landscape, Self::Portrait, Self::Square];
+
+    pub fn from_index(index: u32) -> Self {
+        Self::ALL.get(index as usize).copied().unwrap_or_default()
+    }
+}
+
+pub const MIN_RESOLUTION_LABELS: [&str; 5] = ["Any resolution", "≥ 1280×720", "≥ 1920×1080", "≥ 2560×1440", "≥ 3840×2160"];
+const MIN_RESOLUTIONS: [Option<(i32, i32)>; 5] = [
+    None,
+    Some((1280, 720)),
+    Some((1920, 1080)),
+    Some((2560, 1440)),
+    Some((3840, 2160)),
+];
+
+pub const ASPECT_RATIO_LABELS: [&str; 7] = ["Any aspect", "16:9", "16:10", "21:9", "32:9", "4:3", "9:16"];
+const ASPECT_RATIOS: [Option<f64>; 7] = [
+    None,
+    Some(16.0 / 9.0),
+    Some(16.0 / 10.0),
+    Some(21.0 / 9.0),
+    Some(32.0 / 9.0),
+    Some(4.0 / 3.0),
+    Some(9.0 / 16.0),
+];
+
+pub const FORMAT_LABELS: [&str; 10] = ["Any type", "JPEG", "PNG", "WebP", "JPEG XL", "AVIF", "HEIF", "TIFF", "GIF", "BMP"];
+const FORMATS: [Option<&str>; 10] = [
+    None,
+    Some("jpeg"),
+    Some("png"),
+    Some("webp"),
+    Some("jxl"),
+    Some("avif"),
+    Some("heif"),
+    Some("tiff"),
+    Some("gif"),
+    Some("bmp"),
+];
+
+#[derive(Clone, Default)]
+pub struct ImageFilter {
+    pub query: String,
+    pub orientation: Orientation,
+    pub min_resolution: Option<(i32, i32)>,
+    pub aspect_ratio: Option<f64>,
+    pub format: Option<&'static str>,
+}
+
+impl ImageFilter {
+    pub fn set_min_resolution_index(&mut self, index: u32) {
+        self.min_resolution = MIN_RESOLUTIONS.get(index as usize).copied().flatten();
+    }
+
+    pub fn set_aspect_ratio_index(&mut self, index: u32) {
+        self.aspect_ratio = ASPECT_RATIOS.get(index as usize).copied().flatten();
+    }
+
+    pub fn set_format_index(&mut self, index: u32) {
+        self.format = FORMATS.get(index as usize).copied().flatten();
+    }
+
+    fn needs_image_info(&self) -> bool {
+        self.orientation != Orientation::Any
+            || self.min_resolution.is_some()
+            || self.aspect_ratio.is_some()
+            || self.format.is_some()
+    }
+
+    pub fn matches(&self, item: &WallpaperItem) -> bool {
+        if !fuzzy_match(&self.query, &item.name()) {
+            return false;
+        }
+        if !self.needs_image_info() {
+            return true;
+        }
+        // Hidden until the folder scan has read the header, the filter is re-run once it has.
+        if !item.has_info() {
+            return false;
+        }
+
+        let (width, height) = (item.width(), item.height());
+        let orientation_matches = match self.orientation {
+            Orientation::Any => true,
+            Orientation::Landscape => width > height,
+            Orientation::Portrait => width < height,
+            Orientation::Square => width == height,
+        };
+        let resolution_matches = self
+            .min_resolution
+            .is_none_or(|(min_width, min_height)| width >= min_width && height >= min_height);
+        let aspect_ratio_matches = self
+            .aspect_ratio
+            .is_none_or(|ratio| (item.aspect_ratio() - ratio).abs() < ASPECT_RATIO_TOLERANCE);
+        let format_matches = self.format.is_none_or(|format| item.format() == format);
+
+        orientation_matches && resolution_matches && aspect_ratio_matches && format_matches
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum ImageSort {
+    #[default]
+    Name,
+    Modified,
+    Size,
+    Resolution,
+}
+
+impl ImageSort {
+    pub const LABELS: [&str; 4] = ["Name", "Date modified", "File size", "Resolution"];
+    const ALL: [Self; 4] = [Self::Name, Self::Modified, Self::Size, Self::Resolution];
+
+    pub fn from_index(index: u32) -> Self {
+        Self::ALL.get(index as usize).copied().unwrap_or_default()
+    }
+
+    pub fn compare(&self, a: &WallpaperItem, b: &WallpaperItem) -> Ordering {
+        let ordering = match self {
+            Self::Name => Ordering::Equal,
+            Self::Modified => a.modified().cmp(&b.modified()),
+            Self::Size => a.size().cmp(&b.size()),
+            Self::Resolution => a.pixels().cmp(&b.pixels()),
+        };
+
+        ordering.then_with(|| a.name().to_lowercase().cmp(&b.name().to_lowercase()))
+    }
+}
+
+// Every character of the query has to appear in order, so a plain substring always matches.
+fn fuzzy_match(query: &str, name: &str) -> bool {
+    let name = name.to_lowercase();
+    let mut name_chars = name.chars();
+    query
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .all(|query_char| name_chars.any(|c| c == query_char))
+}
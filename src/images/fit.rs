@@ -0,0 +1,121 @@
+// Scale factors this close to 1 are not worth a mention.
+const SCALE_TOLERANCE: f64 = 0.05;
+const CROP_TOLERANCE: f64 = 0.01;
+// Losing more than this to the crop means the shapes are too different to cover well.
+const CROP_WARNING: f64 = 0.25;
+
+// hyprpaper covers the output: the image is scaled until it fills both sides and the overflow is cut off.
+pub struct Fit {
+    pub scale: f64,
+    pub crop: f64,
+}
+
+impl Fit {
+    pub fn new(image_width: i32, image_height: i32, monitor_width: i32, monitor_height: i32) -> Option<Self> {
+        if image_width <= 0 || image_height <= 0 || monitor_width <= 0 || monitor_height <= 0 {
+            return None;
+        }
+
+        let (image_width, image_height) = (image_width as f64, image_height as f64);
+        let (monitor_width, monitor_height) = (monitor_width as f64, monitor_height as f64);
+        let scale = (monitor_width / image_width).max(monitor_height / image_height);
+        let crop = 1.0 - (monitor_width * monitor_height) / (image_width * scale * image_height * scale);
+        Some(Self { scale, crop })
+    }
+
+    pub fn is_upscaled(&self) -> bool {
+        self.scale > 1.0 + SCALE_TOLERANCE
+    }
+
+    pub fn is_cropped(&self) -> bool {
+        self.crop > CROP_TOLERANCE
+    }
+
+    pub fn needs_warning(&self) -> bool {
+        self.is_upscaled() || self.crop > CROP_WARNING
+    }
+
+    pub fn describe(&self) -> String {
+        let mut description = if self.is_upscaled() {
+            format!("will upscale {}", format_factor(self.scale))
+        } else if self.scale < 1.0 - SCALE_TOLERANCE {
+            format!("will downscale {}", format_factor(1.0 / self.scale))
+        } else {
+            "fits at native size".to_string()
+        };
+        if self.is_cropped() {
+            description.push_str(&format!(", {:.0}% cropped", self.crop * 100.0));
+        }
+        description
+    }
+}
+
+fn format_factor(factor: f64) -> String {
+    if (factor - factor.round()).abs() < SCALE_TOLERANCE {
+        format!("{}×", factor.round())
+    } else {
+        format!("{:.1}×", factor)
+    }
+}
+
+pub fn aspect_ratio_label(width: i32, height: i32) -> String {
+    if width <= 0 || height <= 0 {
+        return String::new();
+    }
+
+    let divisor = greatest_common_divisor(width, height);
+    let (ratio_width, ratio_height) = (width / divisor, height / divisor);
+    if ratio_height <= 32 {
+        format!("{}:{}", ratio_width, ratio_height)
+    } else {
+        format!("{:.2}:1", width as f64 / height as f64)
+    }
+}
+
+fn greatest_common_divisor(a: i32, b: i32) -> i32 {
+    if b == 0 { a } else { greatest_common_divisor(b, a % b) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn describe(image: (i32, i32), monitor: (i32, i32)) -> String {
+        Fit::new(image.0, image.1, monitor.0, monitor.1).unwrap().describe()
+    }
+
+    #[test]
+    fn describes_scaling() {
+        assert_eq!(describe((1920, 1080), (3840, 2160)), "will upscale 2×");
+        assert_eq!(describe((1280, 720), (1920, 1080)), "will upscale 1.5×");
+        assert_eq!(describe((3840, 2160), (1920, 1080)), "will downscale 2×");
+        assert_eq!(describe((1920, 1080), (1920, 1080)), "fits at native size");
+        assert_eq!(describe((1930, 1085), (1920, 1080)), "fits at native size");
+    }
+
+    #[test]
+    fn describes_cropping() {
+        assert_eq!(describe((1920, 1200), (1920, 1080)), "fits at native size, 10% cropped");
+        assert_eq!(describe((1080, 1920), (1920, 1080)), "will upscale 1.8×, 68% cropped");
+    }
+
+    #[test]
+    fn warns_about_upscaling_and_heavy_crops() {
+        let fit = |image: (i32, i32), monitor: (i32, i32)| Fit::new(image.0, image.1, monitor.0, monitor.1).unwrap();
+        assert!(fit((1920, 1080), (3840, 2160)).needs_warning());
+        assert!(fit((2160, 2160), (3840, 1080)).needs_warning());
+        assert!(!fit((3840, 2400), (3840, 2160)).needs_warning());
+        assert!(Fit::new(0, 1080, 1920, 1080).is_none());
+        assert!(Fit::new(1920, 1080, 1920, 0).is_none());
+    }
+
+    #[test]
+    fn labels_aspect_ratios() {
+        assert_eq!(aspect_ratio_label(1920, 1080), "16:9");
+        assert_eq!(aspect_ratio_label(1920, 1200), "8:5");
+        assert_eq!(aspect_ratio_label(3440, 1440), "43:18");
+        assert_eq!(aspect_ratio_label(1080, 1920), "9:16");
+        assert_eq!(aspect_ratio_label(1366, 768), "1.78:1");
+        assert_eq!(aspect_ratio_label(0, 1080), "");
+    }
+}
@@ -0,0 +1,107 @@
+use std::fs;
+
+use gtk::gdk_pixbuf::{Colorspace, Pixbuf};
+use gtk::glib;
+
+use crate::palette::Color;
+use crate::paths::generated_wallpapers_dir;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Fill {
+    Solid,
+    Linear,
+    Radial,
+}
+
+impl Fill {
+    pub const LABELS: [&str; 3] = ["Solid color", "Linear gradient", "Radial gradient"];
+
+    pub fn from_index(index: u32) -> Self {
+        match index {
+            1 => Fill::Linear,
+            2 => Fill::Radial,
+            _ => Fill::Solid,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Generator {
+    pub fill: Fill,
+    pub from: Color,
+    pub to: Color,
+    // Degrees, 0 runs left to right and 90 top to bottom.
+    pub angle: f64,
+}
+
+impl Generator {
+    fn file_name(&self, width: i32, height: i32) -> String {
+        match self.fill {
+            Fill::Solid => format!("solid-{}-{}x{}.png", self.from.hex_digits(), width, height),
+            Fill::Linear => format!(
+                "linear-{}-{}-{}-{}x{}.png",
+                self.from.hex_digits(),
+                self.to.hex_digits(),
+                self.angle.round() as i32,
+                width,
+                height
+            ),
+            Fill::Radial => format!(
+                "radial-{}-{}-{}x{}.png",
+                self.from.hex_digits(),
+                self.to.hex_digits(),
+                width,
+                height
+            ),
+        }
+    }
+
+    fn color_at(&self, x: f64, y: f64, width: f64, height: f64) -> Color {
+        let (dx, dy) = (x - width / 2.0, y - height / 2.0);
+        let amount = match self.fill {
+            Fill::Solid => return self.from,
+            Fill::Linear => {
+                let (sin, cos) = self.angle.to_radians().sin_cos();
+                // Half the length of the gradient line, so both corners on it get the end colors.
+                let extent = (cos.abs() * width + sin.abs() * height) / 2.0;
+                0.5 + (dx * cos + dy * sin) / (2.0 * extent)
+            }
+            Fill::Radial => (dx * dx + dy * dy).sqrt() / (width * width + height * height).sqrt() * 2.0,
+        };
+        self.from.mix(self.to, amount.clamp(0.0, 1.0))
+    }
+
+    // Renders once per size, later calls reuse the file in the cache.
+    pub fn render(&self, width: i32, height: i32) -> Result<String, String> {
+        let dir = generated_wallpapers_dir();
+        let path = dir.join(self.file_name(width, height));
+        let path_str = path.to_string_lossy().to_string();
+        if path.exists() {
+            return Ok(path_str);
+        }
+
+        let mut pixels = Vec::with_capacity(width as usize * height as usize * 3);
+        for y in 0..height {
+            for x in 0..width {
+                let color = self.color_at(x as f64 + 0.5, y as f64 + 0.5, width as f64, height as f64);
+                pixels.extend_from_slice(&[color.red, color.green, color.blue]);
+            }
+        }
+
+        let pixbuf = Pixbuf::from_bytes(
+            &glib::Bytes::from_owned(pixels),
+            Colorspace::Rgb,
+            false,
+            8,
+            width,
+            height,
+            width * 3,
+        );
+
+        fs::create_dir_all(&dir).map_err(|err| format!("Could not create {}: {}", dir.display(), err))?;
+        pixbuf
+            .savev(&path, "png", &[])
+            .map_err(|err| format!("Could not save {}: {}", path_str, err))?;
+        Ok(path_str)
+    }
+}
@@ -0,0 +1,45 @@
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+
+use gtk::gdk_pixbuf::Pixbuf;
+
+use crate::images::scan::is_video;
+
+pub struct ImageInfo {
+    pub width: i32,
+    pub height: i32,
+    pub size: u64,
+    pub modified: SystemTime,
+    pub format: String,
+}
+
+impl ImageInfo {
+    // Only the image header is decoded, the pixels are never loaded.
+    pub fn read(path: &str) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        // Videos have no size that can be read cheaply, they are still listed.
+        let (format, width, height) = match Pixbuf::file_info(path) {
+            Some((format, width, height)) => (format.name(), width, height),
+            None if is_video(path) => (None, 0, 0),
+            None => return None,
+        };
+
+        let format = format
+            .map(|name| name.to_lowercase())
+            .or_else(|| {
+                Path::new(path)
+                    .extension()
+                    .map(|extension| extension.to_string_lossy().to_lowercase())
+            })
+            .unwrap_or_default();
+
+        Some(Self {
+            width,
+            height,
+            size: metadata.len(),
+            modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            format,
+        })
+    }
+}
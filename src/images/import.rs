@@ -0,0 +1,78 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use gio::{File, FileCopyFlags, glib};
+use gtk::prelude::*;
+
+use crate::images::scan::{HEADER_SIZE, guess_content_type, is_supported_media, is_supported_media_content_type};
+use crate::paths::managed_wallpapers_dir;
+
+// Of several dropped files only the first supported one is imported, the rest are never copied.
+pub async fn import_first_image(files: Vec<File>, current_dir: String) -> Option<String> {
+    for file in files {
+        if is_supported_file(&file).await {
+            return import_image(&file, &current_dir).await;
+        }
+    }
+    None
+}
+
+// Images from outside the browsed directory are copied into the managed wallpaper folder,
+// so the config never points at a download folder or a remote location.
+async fn import_image(file: &File, current_dir: &str) -> Option<String> {
+    if let Some(path) = file.path()
+        && (path.parent() == Some(Path::new(current_dir)) || path.starts_with(managed_wallpapers_dir()))
+    {
+        return Some(path.to_string_lossy().to_string());
+    }
+
+    let wallpapers_dir = managed_wallpapers_dir();
+    if let Err(err) = fs::create_dir_all(&wallpapers_dir) {
+        eprintln!("Could not create {}: {}", wallpapers_dir.display(), err);
+        return None;
+    }
+
+    let file_name = file.basename()?.to_string_lossy().to_string();
+    let destination = unique_destination(&wallpapers_dir, &file_name);
+    let (copy, _progress) = file.copy_future(&File::for_path(&destination), FileCopyFlags::NONE, glib::Priority::DEFAULT);
+    match copy.await {
+        Ok(()) => Some(destination.to_string_lossy().to_string()),
+        Err(err) => {
+            eprintln!("Could not copy {}: {}", file.uri(), err);
+            None
+        }
+    }
+}
+
+// The content decides, images dragged out of a browser often have no extension.
+async fn is_supported_file(file: &File) -> bool {
+    if let Some(path) = file.path() {
+        let path = path.to_string_lossy().to_string();
+        return gio::spawn_blocking(move || is_supported_media(&path)).await.unwrap_or(false);
+    }
+
+    let Ok(stream) = file.read_future(glib::Priority::DEFAULT).await else {
+        return false;
+    };
+    let Ok((header, read)) = stream.read_future(vec![0; HEADER_SIZE], glib::Priority::DEFAULT).await else {
+        return false;
+    };
+    let name = file.basename().map(|name| name.to_string_lossy().to_string());
+    is_supported_media_content_type(&guess_content_type(name.as_deref(), &header[..read]))
+}
+
+fn unique_destination(dir: &Path, file_name: &str) -> PathBuf {
+    let destination = dir.join(file_name);
+    if !destination.exists() {
+        return destination;
+    }
+
+    let (stem, extension) = match file_name.rsplit_once('.') {
+        Some((stem, extension)) => (stem, format!(".{}", extension)),
+        None => (file_name, String::new()),
+    };
+    (1..)
+        .map(|n| dir.join(format!("{}-{}{}", stem, n, extension)))
+        .find(|candidate| !candidate.exists())
+        .unwrap()
+}
@@ -0,0 +1,12 @@
+pub mod assign;
+pub mod crop;
+pub mod duplicates;
+pub mod exif;
+pub mod filter;
+pub mod fit;
+pub mod generate;
+pub mod image_info;
+pub mod import;
+pub mod scan;
+pub mod transcode;
+pub mod wallpaper_item;
@@ -0,0 +1,132 @@
+use std::fs::{self, File};
+use std::io::Read;
+use std::sync::OnceLock;
+
+use gtk::gdk_pixbuf::Pixbuf;
+
+// Enough for the magic of every format shared-mime-info knows.
+pub const HEADER_SIZE: usize = 4096;
+
+// Detected from the file's magic bytes, the name only decides when the content is ambiguous.
+fn content_type(path: &str) -> Option<String> {
+    let mut header = vec![0; HEADER_SIZE];
+    let read = File::open(path).and_then(|mut file| file.read(&mut header)).ok()?;
+    Some(guess_content_type(Some(path), &header[..read]))
+}
+
+pub fn guess_content_type(name: Option<&str>, header: &[u8]) -> String {
+    let (content_type, _) = gio::content_type_guess(name, header);
+    content_type.to_string()
+}
+
+fn has_media_type(content_type: &str, media_type: &str) -> bool {
+    gio::content_type_get_mime_type(content_type).is_some_and(|mime_type| mime_type.starts_with(media_type))
+}
+
+pub fn image_content_type(path: &str) -> Option<String> {
+    content_type(path).filter(|content_type| has_media_type(content_type, "image/"))
+}
+
+// GIFs count as animated as well, the video backend plays them like any clip.
+pub fn is_animated_content_type(content_type: &str) -> bool {
+    has_media_type(content_type, "video/") || gio::content_type_is_a(content_type, "image/gif")
+}
+
+pub fn is_animated(path: &str) -> bool {
+    content_type(path).is_some_and(|content_type| is_animated_content_type(&content_type))
+}
+
+pub fn is_video(path: &str) -> bool {
+    content_type(path).is_some_and(|content_type| has_media_type(&content_type, "video/"))
+}
+
+// Every format a gdk-pixbuf loader is installed for, such as AVIF, HEIF or JPEG XL.
+fn decodable_content_types() -> &'static [String] {
+    static CONTENT_TYPES: OnceLock<Vec<String>> = OnceLock::new();
+    CONTENT_TYPES.get_or_init(|| {
+        Pixbuf::formats()
+            .iter()
+            .flat_map(|format| format.mime_types())
+            .map(|mime_type| mime_type.to_string())
+            .collect()
+    })
+}
+
+pub fn is_decodable(content_type: &str) -> bool {
+    decodable_content_types()
+        .iter()
+        .any(|supported| gio::content_type_is_a(content_type, supported))
+}
+
+pub fn is_supported_image(path: &str) -> bool {
+    image_content_type(path).is_some_and(|content_type| is_decodable(&content_type))
+}
+
+// Anything the grid lists: still images and what the video backend plays.
+pub fn is_supported_media_content_type(content_type: &str) -> bool {
+    is_animated_content_type(content_type) || (has_media_type(content_type, "image/") && is_decodable(content_type))
+}
+
+pub fn is_supported_media(path: &str) -> bool {
+    content_type(path).is_some_and(|content_type| is_supported_media_content_type(&content_type))
+}
+
+pub fn read_image_entries(path: &str) -> Vec<String> {
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut files = Vec::new();
+
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(_) => continue,
+        };
+
+        if entry.file_type().map_or(true, |ft| ft.is_dir()) {
+            continue;
+        }
+
+        let metadata = match entry.metadata() {
+            Ok(metadata) => metadata,
+            Err(_) => continue,
+        };
+
+        if !metadata.is_file() {
+            continue;
+        }
+
+        let file_name = entry.file_name();
+        let file_name_str = match file_name.to_str() {
+            Some(name) => name,
+            None => continue,
+        };
+
+        let full_path = format!("{}/{}", path, file_name_str);
+        if is_supported_media(&full_path) {
+            files.push(full_path);
+        }
+    }
+
+    files
+}
+
+// The same scan, descending into subdirectories as well.
+pub fn read_image_entries_recursive(path: &str) -> Vec<String> {
+    let mut files = read_image_entries(path);
+
+    if let Ok(entries) = fs::read_dir(path) {
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            if entry.file_type().is_ok_and(|ft| ft.is_dir())
+                && let Some(name) = entry.file_name().to_str()
+                && !name.starts_with('.')
+            {
+                files.extend(read_image_entries_recursive(&format!("{}/{}", path, name)));
+            }
+        }
+    }
+
+    files
+}
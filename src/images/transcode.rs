@@ -0,0 +1,50 @@
+use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use gtk::gdk_pixbuf::Pixbuf;
+
+use crate::images::scan::image_content_type;
+use crate::paths::transcoded_wallpapers_dir;
+
+// hyprpaper decodes these itself, anything else is handed over as a PNG copy.
+const HYPRPAPER_CONTENT_TYPES: [&str; 4] = ["image/png", "image/jpeg", "image/webp", "image/jxl"];
+
+pub fn is_native_to_hyprpaper(path: &str) -> bool {
+    image_content_type(path).is_some_and(|content_type| {
+        HYPRPAPER_CONTENT_TYPES
+            .iter()
+            .any(|native| gio::content_type_is_a(&content_type, native))
+    })
+}
+
+pub fn transcoded_path(path: &str) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    let stem = Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    transcoded_wallpapers_dir().join(format!("{}-{:016x}.png", stem, hasher.finish()))
+}
+
+// The copy is made again whenever the original is newer than it.
+pub fn backend_path(path: &str) -> Result<String, String> {
+    if is_native_to_hyprpaper(path) {
+        return Ok(path.to_owned());
+    }
+
+    let destination = transcoded_path(path);
+    let modified = |path: &Path| fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+    if modified(&destination).is_some_and(|transcoded| modified(Path::new(path)).is_some_and(|original| transcoded >= original)) {
+        return Ok(destination.to_string_lossy().to_string());
+    }
+
+    let pixbuf = Pixbuf::from_file(path).map_err(|err| format!("Could not load {}: {}", path, err))?;
+    fs::create_dir_all(transcoded_wallpapers_dir())
+        .map_err(|err| format!("Could not create {}: {}", transcoded_wallpapers_dir().display(), err))?;
+    pixbuf
+        .savev(&destination, "png", &[])
+        .map_err(|err| format!("Could not convert {}: {}", path, err))?;
+    Ok(destination.to_string_lossy().to_string())
+}
@@ -0,0 +1,133 @@
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use gtk::glib;
+use gtk::glib::subclass::prelude::*;
+
+use crate::images::image_info::ImageInfo;
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use gtk::glib;
+    use gtk::glib::Properties;
+    use gtk::glib::prelude::*;
+    use gtk::glib::subclass::prelude::*;
+
+    #[derive(Properties, Default)]
+    #[properties(wrapper_type = super::WallpaperItem)]
+    pub struct WallpaperItem {
+        #[property(get, construct_only)]
+        pub path: RefCell<String>,
+        #[property(get)]
+        pub name: RefCell<String>,
+        #[property(get)]
+        pub width: Cell<i32>,
+        #[property(get)]
+        pub height: Cell<i32>,
+        #[property(get)]
+        pub size: Cell<u64>,
+        #[property(get)]
+        pub modified: Cell<i64>,
+        #[property(get)]
+        pub format: RefCell<String>,
+        #[property(get, set)]
+        pub favorite: Cell<bool>,
+        #[property(get, set)]
+        pub active_monitors: RefCell<Vec<String>>,
+        #[property(get)]
+        pub animated: Cell<bool>,
+        #[property(get)]
+        pub video: Cell<bool>,
+
+        pub info_loaded: Cell<bool>,
+        pub has_info: Cell<bool>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for WallpaperItem {
+        const NAME: &'static str = "UltrapaperWallpaperItem";
+        type Type = super::WallpaperItem;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for WallpaperItem {}
+}
+
+glib::wrapper! {
+    pub struct WallpaperItem(ObjectSubclass<imp::WallpaperItem>);
+}
+
+impl WallpaperItem {
+    pub fn new(path: &str) -> Self {
+        let item: Self = glib::Object::builder().property("path", path).build();
+        let name = Path::new(path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_owned());
+        item.imp().name.replace(name);
+        item
+    }
+
+    // Reads the image header right away unless the folder scan already delivered it.
+    pub fn ensure_info(&self) -> bool {
+        if !self.imp().info_loaded.get() {
+            self.set_info(ImageInfo::read(&self.path()));
+        }
+        self.imp().has_info.get()
+    }
+
+    pub fn info_loaded(&self) -> bool {
+        self.imp().info_loaded.get()
+    }
+
+    pub fn has_info(&self) -> bool {
+        self.imp().has_info.get()
+    }
+
+    pub fn set_info(&self, info: Option<ImageInfo>) {
+        let imp = self.imp();
+        imp.info_loaded.set(true);
+        let Some(info) = info else {
+            return;
+        };
+
+        imp.width.set(info.width);
+        imp.height.set(info.height);
+        imp.size.set(info.size);
+        imp.modified.set(
+            info.modified
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs() as i64)
+                .unwrap_or_default(),
+        );
+        imp.format.replace(info.format);
+        imp.has_info.set(true);
+
+        self.notify_width();
+        self.notify_height();
+        self.notify_size();
+        self.notify_modified();
+        self.notify_format();
+    }
+
+    // Sniffed with the headers in the background, until then the item is taken for a still image.
+    pub fn set_media(&self, animated: bool, video: bool) {
+        let imp = self.imp();
+        imp.animated.set(animated);
+        imp.video.set(video);
+        self.notify_animated();
+        self.notify_video();
+    }
+
+    pub fn aspect_ratio(&self) -> f64 {
+        if self.height() == 0 {
+            return 0.0;
+        }
+        self.width() as f64 / self.height() as f64
+    }
+
+    pub fn pixels(&self) -> i64 {
+        self.width() as i64 * self.height() as i64
+    }
+}
@@ -1,19 +1,46 @@
+use std::env;
+
 use gtk::gdk::Display;
 use gtk::prelude::*;
+use gtk::gio::ApplicationFlags;
 use gtk::{Application, ApplicationWindow, CssProvider, glib};
 
 use crate::hypr::hyprctl;
 use crate::hypr::hyprpaper::HyprpaperConfig;
-use crate::state::{set_config, set_monitors};
+use crate::settings::{MonitorIdentifier, get_settings, update_settings};
+use crate::profiles::{apply_profile, find_profile_for_monitors};
+use crate::state::{
+    get_first_wallpaper_path, get_monitor_details, migrate_monitor_identifiers, save_config, set_config, set_monitors, set_preloads_from_wallpapers
+};
+use crate::widgets::image_card::update_thumbnail_size;
 use crate::windows::main_window::MainWindow;
+use crate::windows::picker_window::show_picker_windows;
+use crate::windows::profiles_menu::add_profile_actions;
+mod app_model;
+mod cli;
+mod desktop;
+mod favorites;
+mod hooks;
 mod hypr;
+mod images;
+mod palette;
+mod paths;
+mod post_apply;
+mod power;
+mod profiles;
+mod settings;
 mod widgets;
 mod windows;
 mod state;
 
-const APP_ID: &str = "sa.ahmedy.ultrapaper";
+pub const APP_ID: &str = "sa.ahmedy.ultrapaper";
 
 fn main() -> glib::ExitCode {
+    let args: Vec<String> = env::args().collect();
+    if let Some(exit_code) = cli::run(&args) {
+        return exit_code;
+    }
+
     let hyprpaper_config = match HyprpaperConfig::new(HyprpaperConfig::get_default_config_path()) {
         Ok(config) => config,
         Err(err) => panic!("Error loading config: {}", err),
@@ -24,14 +51,48 @@ fn main() -> glib::ExitCode {
     let monitors = hyprctl::get_monitors();
     set_monitors(monitors);
 
+    if get_settings().monitor_identifier == MonitorIdentifier::Description && migrate_monitor_identifiers() {
+        set_preloads_from_wallpapers();
+        save_config();
+    }
+
+    // The picker runs next to a main window that may already be open, GTK never sees its arguments.
+    if args.get(1).is_some_and(|arg| arg == "--picker") {
+        let dir = args.get(2).cloned().unwrap_or_else(get_first_wallpaper_path);
+        let app = Application::builder()
+            .application_id(APP_ID)
+            .flags(ApplicationFlags::NON_UNIQUE)
+            .build();
+        app.connect_startup(|_| load_css());
+        app.connect_activate(move |app| show_picker_windows(app, &dir));
+        return app.run_with_args(&args[..1]);
+    }
+
     let app = Application::builder().application_id(APP_ID).build();
 
-    app.connect_startup(|_| load_css());
+    // Only the primary instance gets here, others just hand their activation to it.
+    app.connect_startup(|app| {
+        load_css();
+        add_profile_actions(app);
+        apply_matching_profile(app);
+    });
     app.connect_activate(build_ui);
 
     app.run()
 }
 
+fn apply_matching_profile(app: &Application) {
+    if !get_settings().auto_profile {
+        return;
+    }
+    if let Some(name) = find_profile_for_monitors(&get_monitor_details()) {
+        match apply_profile(&name) {
+            Ok(post_apply) => post_apply.hold_application(app),
+            Err(err) => eprintln!("{}", err),
+        }
+    }
+}
+
 fn load_css() {
     let provider = CssProvider::new();
     provider.load_from_string(include_str!("style.css"));
@@ -41,13 +102,29 @@ fn load_css() {
         &provider,
         gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
     );
+    update_thumbnail_size();
 }
 
 fn build_ui(app: &Application) {
+    let settings = get_settings();
     let window = ApplicationWindow::builder()
         .application(app)
         .title("Ultrapaper")
+        .maximized(settings.window_maximized)
         .build();
+    if let Some((width, height)) = settings.window_size {
+        window.set_default_size(width, height);
+    }
+    // The unmaximized size is kept, so leaving a maximized window restores it.
+    window.connect_close_request(|window| {
+        let (width, height) = window.default_size();
+        let maximized = window.is_maximized();
+        update_settings(|settings| {
+            settings.window_size = Some((width, height));
+            settings.window_maximized = maximized;
+        });
+        glib::Propagation::Proceed
+    });
 
     let main_window = MainWindow::new(&window);
 
@@ -0,0 +1,121 @@
+use std::fmt::Write;
+use std::fs;
+
+use crate::palette::Palette;
+use crate::palette::extract::extract_palette;
+use crate::paths::{cache_dir, write_atomically};
+
+const ANSI_NAMES: [&str; 8] = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white"];
+
+pub fn export_palette(path: &str) {
+    let Some(palette) = extract_palette(path) else {
+        return;
+    };
+
+    let dir = cache_dir();
+    let templates = [
+        ("colors.json", to_json(&palette)),
+        ("colors-hyprland.conf", to_hyprland(&palette)),
+        ("colors-waybar.css", to_css(&palette)),
+        ("colors-kitty.conf", to_kitty(&palette)),
+        ("colors-foot.ini", to_foot(&palette)),
+        ("colors-alacritty.toml", to_alacritty(&palette)),
+    ];
+
+    let result = fs::create_dir_all(&dir).and_then(|_| {
+        templates
+            .iter()
+            .try_for_each(|(file_name, content)| write_atomically(&dir.join(file_name), content))
+    });
+    if let Err(err) = result {
+        eprintln!("Could not export the palette to {}: {}", dir.display(), err);
+    }
+}
+
+fn to_json(palette: &Palette) -> String {
+    let mut json = String::from("{\n");
+    let _ = writeln!(json, "  \"wallpaper\": \"{}\",", escape_json(&palette.wallpaper));
+    json.push_str("  \"alpha\": \"100\",\n");
+    json.push_str("  \"special\": {\n");
+    let _ = writeln!(json, "    \"background\": \"{}\",", palette.background().hex());
+    let _ = writeln!(json, "    \"foreground\": \"{}\",", palette.foreground().hex());
+    let _ = writeln!(json, "    \"cursor\": \"{}\"", palette.foreground().hex());
+    json.push_str("  },\n");
+    json.push_str("  \"colors\": {\n");
+    for (index, color) in palette.colors.iter().enumerate() {
+        let separator = if index + 1 < palette.colors.len() { "," } else { "" };
+        let _ = writeln!(json, "    \"color{}\": \"{}\"{}", index, color.hex(), separator);
+    }
+    json.push_str("  }\n}\n");
+    json
+}
+
+fn escape_json(value: &str) -> String {
+    let mut escaped = String::new();
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn to_hyprland(palette: &Palette) -> String {
+    let mut conf = String::new();
+    let _ = writeln!(conf, "$background = rgb({})", palette.background().hex_digits());
+    let _ = writeln!(conf, "$foreground = rgb({})", palette.foreground().hex_digits());
+    for (index, color) in palette.colors.iter().enumerate() {
+        let _ = writeln!(conf, "$color{} = rgb({})", index, color.hex_digits());
+    }
+    conf
+}
+
+fn to_css(palette: &Palette) -> String {
+    let mut css = String::new();
+    let _ = writeln!(css, "@define-color background {};", palette.background().hex());
+    let _ = writeln!(css, "@define-color foreground {};", palette.foreground().hex());
+    for (index, color) in palette.colors.iter().enumerate() {
+        let _ = writeln!(css, "@define-color color{} {};", index, color.hex());
+    }
+    css
+}
+
+fn to_kitty(palette: &Palette) -> String {
+    let mut conf = String::new();
+    let _ = writeln!(conf, "background {}", palette.background().hex());
+    let _ = writeln!(conf, "foreground {}", palette.foreground().hex());
+    let _ = writeln!(conf, "cursor {}", palette.foreground().hex());
+    for (index, color) in palette.colors.iter().enumerate() {
+        let _ = writeln!(conf, "color{} {}", index, color.hex());
+    }
+    conf
+}
+
+fn to_foot(palette: &Palette) -> String {
+    let mut ini = String::from("[colors]\n");
+    let _ = writeln!(ini, "background={}", palette.background().hex_digits());
+    let _ = writeln!(ini, "foreground={}", palette.foreground().hex_digits());
+    for (index, color) in palette.colors.iter().enumerate() {
+        let (kind, number) = if index < 8 { ("regular", index) } else { ("bright", index - 8) };
+        let _ = writeln!(ini, "{}{}={}", kind, number, color.hex_digits());
+    }
+    ini
+}
+
+fn to_alacritty(palette: &Palette) -> String {
+    let mut toml = String::from("[colors.primary]\n");
+    let _ = writeln!(toml, "background = \"{}\"", palette.background().hex());
+    let _ = writeln!(toml, "foreground = \"{}\"", palette.foreground().hex());
+    for (section, colors) in [("normal", &palette.colors[..8]), ("bright", &palette.colors[8..])] {
+        let _ = writeln!(toml, "\n[colors.{}]", section);
+        for (name, color) in ANSI_NAMES.iter().zip(colors) {
+            let _ = writeln!(toml, "{} = \"{}\"", name, color.hex());
+        }
+    }
+    toml
+}
@@ -0,0 +1,117 @@
+use gtk::gdk_pixbuf::Pixbuf;
+
+use crate::palette::{Color, Palette, WHITE};
+
+const SAMPLE_SIZE: i32 = 128;
+const BASE_COLORS: usize = 8;
+const BRIGHT_MIX: f64 = 0.25;
+
+pub fn extract_palette(path: &str) -> Option<Palette> {
+    let pixbuf = match Pixbuf::from_file_at_scale(path, SAMPLE_SIZE, SAMPLE_SIZE, true) {
+        Ok(pixbuf) => pixbuf,
+        Err(err) => {
+            eprintln!("Could not load {} for the palette: {}", path, err);
+            return None;
+        }
+    };
+
+    let pixels = read_pixels(&pixbuf);
+    if pixels.is_empty() {
+        return None;
+    }
+
+    let mut base_colors = median_cut(pixels, BASE_COLORS);
+    base_colors.sort_by(|a, b| a.luminance().total_cmp(&b.luminance()));
+    while base_colors.len() < BASE_COLORS {
+        base_colors.push(*base_colors.last().unwrap());
+    }
+
+    let mut colors = [base_colors[0]; 16];
+    for (index, color) in base_colors.iter().enumerate() {
+        colors[index] = *color;
+        colors[index + BASE_COLORS] = color.mix(WHITE, BRIGHT_MIX);
+    }
+
+    Some(Palette {
+        wallpaper: path.to_owned(),
+        colors,
+    })
+}
+
+fn read_pixels(pixbuf: &Pixbuf) -> Vec<[u8; 3]> {
+    let bytes = pixbuf.read_pixel_bytes();
+    let n_channels = pixbuf.n_channels() as usize;
+    let rowstride = pixbuf.rowstride() as usize;
+    let has_alpha = pixbuf.has_alpha();
+
+    let mut pixels = Vec::new();
+    for y in 0..pixbuf.height() as usize {
+        for x in 0..pixbuf.width() as usize {
+            let offset = y * rowstride + x * n_channels;
+            let Some(pixel) = bytes.get(offset..offset + n_channels) else {
+                continue;
+            };
+            if has_alpha && pixel[3] < 128 {
+                continue;
+            }
+            pixels.push([pixel[0], pixel[1], pixel[2]]);
+        }
+    }
+    pixels
+}
+
+// Repeatedly splits the box with the widest channel range at its median until there are enough boxes.
+fn median_cut(pixels: Vec<[u8; 3]>, count: usize) -> Vec<Color> {
+    let mut boxes = vec![pixels];
+
+    while boxes.len() < count {
+        let widest = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, pixels)| pixels.len() > 1)
+            .map(|(index, pixels)| {
+                let (channel, range) = widest_channel(pixels);
+                (index, channel, range)
+            })
+            .max_by_key(|(_, _, range)| *range);
+
+        let Some((index, channel, _)) = widest else {
+            break;
+        };
+
+        let mut pixels = boxes.swap_remove(index);
+        pixels.sort_unstable_by_key(|pixel| pixel[channel]);
+        let upper = pixels.split_off(pixels.len() / 2);
+        boxes.push(pixels);
+        boxes.push(upper);
+    }
+
+    boxes.iter().map(|pixels| average(pixels)).collect()
+}
+
+fn widest_channel(pixels: &[[u8; 3]]) -> (usize, u8) {
+    (0..3)
+        .map(|channel| {
+            let min = pixels.iter().map(|pixel| pixel[channel]).min().unwrap_or(0);
+            let max = pixels.iter().map(|pixel| pixel[channel]).max().unwrap_or(0);
+            (channel, max - min)
+        })
+        .max_by_key(|(_, range)| *range)
+        .unwrap()
+}
+
+fn average(pixels: &[[u8; 3]]) -> Color {
+    let count = pixels.len().max(1) as u64;
+    let sum = pixels.iter().fold([0u64; 3], |sum, pixel| {
+        [
+            sum[0] + pixel[0] as u64,
+            sum[1] + pixel[1] as u64,
+            sum[2] + pixel[2] as u64,
+        ]
+    });
+    Color {
+        red: (sum[0] / count) as u8,
+        green: (sum[1] / count) as u8,
+        blue: (sum[2] / count) as u8,
+    }
+}
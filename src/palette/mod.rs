@@ -0,0 +1,55 @@
+pub mod export;
+pub mod extract;
+
+#[derive(Clone, Copy)]
+pub struct Color {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+impl Color {
+    pub fn hex(&self) -> String {
+        format!("#{}", self.hex_digits())
+    }
+
+    pub fn hex_digits(&self) -> String {
+        format!("{:02x}{:02x}{:02x}", self.red, self.green, self.blue)
+    }
+
+    pub fn luminance(&self) -> f64 {
+        0.2126 * self.red as f64 + 0.7152 * self.green as f64 + 0.0722 * self.blue as f64
+    }
+
+    pub fn mix(&self, other: Color, amount: f64) -> Color {
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * amount).round() as u8;
+        Color {
+            red: mix(self.red, other.red),
+            green: mix(self.green, other.green),
+            blue: mix(self.blue, other.blue),
+        }
+    }
+}
+
+pub const WHITE: Color = Color {
+    red: 255,
+    green: 255,
+    blue: 255,
+};
+
+// Follows the layout pywal uses: color0 is the background, color7 the foreground
+// and color8 to color15 are brighter variants of color0 to color7.
+pub struct Palette {
+    pub wallpaper: String,
+    pub colors: [Color; 16],
+}
+
+impl Palette {
+    pub fn background(&self) -> Color {
+        self.colors[0]
+    }
+
+    pub fn foreground(&self) -> Color {
+        self.colors[7]
+    }
+}
@@ -0,0 +1,86 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use gtk::glib;
+
+const APP_DIR_NAME: &str = "ultrapaper";
+
+pub fn managed_wallpapers_dir() -> PathBuf {
+    glib::user_data_dir().join(APP_DIR_NAME).join("wallpapers")
+}
+
+pub fn config_dir() -> PathBuf {
+    glib::user_config_dir().join(APP_DIR_NAME)
+}
+
+pub fn favorites_file() -> PathBuf {
+    config_dir().join("favorites")
+}
+
+pub fn animated_wallpapers_file() -> PathBuf {
+    config_dir().join("animated")
+}
+
+pub fn profiles_dir() -> PathBuf {
+    config_dir().join("profiles")
+}
+
+pub fn cache_dir() -> PathBuf {
+    glib::user_cache_dir().join(APP_DIR_NAME)
+}
+
+pub fn generated_wallpapers_dir() -> PathBuf {
+    cache_dir().join("generated")
+}
+
+pub fn transcoded_wallpapers_dir() -> PathBuf {
+    cache_dir().join("transcoded")
+}
+
+pub fn video_frames_dir() -> PathBuf {
+    cache_dir().join("frames")
+}
+
+pub fn image_hashes_file() -> PathBuf {
+    cache_dir().join("hashes")
+}
+
+pub fn runtime_dir() -> PathBuf {
+    glib::user_runtime_dir().join(APP_DIR_NAME)
+}
+
+pub fn settings_file() -> PathBuf {
+    config_dir().join("ultrapaper.conf")
+}
+
+pub fn hooks_log_file() -> PathBuf {
+    cache_dir().join("hooks.log")
+}
+
+// Readers never see a half written file: the content goes to a temporary file next to the target,
+// which then replaces it. A symlinked target stays a symlink, the file it points to is replaced.
+pub fn write_atomically(path: &Path, content: impl AsRef<[u8]>) -> io::Result<()> {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let file_name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let temporary = path.with_file_name(format!(".{}.{}.tmp", file_name, process::id()));
+    fs::write(&temporary, content)
+        .and_then(|_| match fs::metadata(&path) {
+            Ok(metadata) => fs::set_permissions(&temporary, metadata.permissions()),
+            Err(_) => Ok(()),
+        })
+        .and_then(|_| fs::rename(&temporary, &path))
+        .inspect_err(|_| {
+            let _ = fs::remove_file(&temporary);
+        })
+}
+
+// An empty directory of its own for a test, under the system's temporary directory.
+#[cfg(test)]
+pub fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ultrapaper-test-{}-{}", process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
@@ -0,0 +1,85 @@
+use std::sync::OnceLock;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use gio::glib;
+use gio::prelude::*;
+use gtk::Application;
+
+use crate::hooks::run_hooks;
+use crate::hypr::hyprctl::Monitor;
+use crate::hypr::hyprlock;
+use crate::palette::export::export_palette;
+use crate::settings::get_settings;
+use crate::state::get_monitor_details;
+
+type Job = (Vec<(String, String)>, Vec<Monitor>, Sender<()>);
+
+// Resolves once the work for one apply is done.
+pub struct PostApply(Receiver<()>);
+
+impl PostApply {
+    // For an apply that runs later, the sender goes to `send_post_apply` once it has run. Dropping
+    // it resolves the PostApply too.
+    pub fn pending() -> (Sender<()>, PostApply) {
+        let (done, finished) = mpsc::channel();
+        (done, PostApply(finished))
+    }
+
+    pub fn wait(self) {
+        let _ = self.0.recv();
+    }
+
+    pub async fn finished(self) {
+        let _ = gio::spawn_blocking(move || self.wait()).await;
+    }
+
+    // Closing the last window doesn't end the process while the apply, hooks or the hyprlock sync still run.
+    pub fn hold_application(self, app: &Application) {
+        let hold = app.hold();
+        glib::spawn_future_local(async move {
+            self.finished().await;
+            drop(hold);
+        });
+    }
+}
+
+// Work that reacts to a new wallpaper runs in the background so applying stays instant. A single
+// thread does it in the order the wallpapers were applied, two quick applies never write the same files at once.
+pub fn send_post_apply(wallpapers: Vec<(String, String)>, done: Sender<()>) {
+    static WORKER: OnceLock<Sender<Job>> = OnceLock::new();
+    let worker = WORKER.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        thread::spawn(move || {
+            for (wallpapers, monitors, done) in receiver {
+                for (monitor, path) in wallpapers {
+                    post_apply(&monitors, &monitor, &path);
+                }
+                let _ = done.send(());
+            }
+        });
+        sender
+    });
+
+    // The model lives on this thread, the worker gets the monitors as they are now.
+    let monitors = get_monitor_details();
+    if let Err(mpsc::SendError((wallpapers, monitors, done))) = worker.send((wallpapers, monitors, done)) {
+        eprintln!("The post-apply worker stopped, running it here instead");
+        for (monitor, path) in wallpapers {
+            post_apply(&monitors, &monitor, &path);
+        }
+        let _ = done.send(());
+    }
+}
+
+fn post_apply(monitors: &[Monitor], monitor: &str, path: &str) {
+    export_palette(path);
+
+    let settings = get_settings();
+    if settings.hyprlock_sync {
+        let config_path = settings.hyprlock_config.unwrap_or_else(hyprlock::get_default_config_path);
+        hyprlock::sync_background(&config_path, monitors, monitor, path);
+    }
+
+    run_hooks(monitor, path);
+}
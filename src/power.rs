@@ -0,0 +1,23 @@
+use std::fs;
+
+const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
+
+// Without any mains supply listed this is a desktop, which never runs on battery.
+pub fn is_on_battery() -> bool {
+    let Ok(entries) = fs::read_dir(POWER_SUPPLY_DIR) else {
+        return false;
+    };
+
+    let mut has_mains = false;
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        let read = |name: &str| fs::read_to_string(path.join(name)).map(|value| value.trim().to_string());
+        if read("type").is_ok_and(|kind| kind == "Mains") {
+            has_mains = true;
+            if read("online").is_ok_and(|online| online == "1") {
+                return false;
+            }
+        }
+    }
+    has_mains
+}
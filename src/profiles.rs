@@ -0,0 +1,95 @@
+use std::fs;
+
+use crate::hypr::hyprctl::{Monitor, apply_wallpapers};
+use crate::hypr::hyprlang::{Line, escape, parse_line};
+use crate::hypr::hyprpaper::WallpaperEntry;
+use crate::hypr::mpvpaper::source_path;
+use crate::paths::profiles_dir;
+use crate::post_apply::PostApply;
+
+const PROFILE_EXTENSION: &str = "conf";
+
+pub fn list_profiles() -> Vec<String> {
+    let Ok(entries) = fs::read_dir(profiles_dir()) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == PROFILE_EXTENSION))
+        .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().to_string()))
+        .collect();
+    names.sort();
+    names
+}
+
+fn check_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() || name.contains('/') || name.starts_with('.') {
+        return Err(format!("\"{}\" is not a valid profile name", name));
+    }
+    Ok(())
+}
+
+pub fn read_profile(name: &str) -> Result<Vec<WallpaperEntry>, String> {
+    check_name(name)?;
+    let path = profiles_dir().join(format!("{}.{}", name, PROFILE_EXTENSION));
+    let content = fs::read_to_string(&path).map_err(|err| format!("Could not read profile {}: {}", name, err))?;
+
+    // Profiles use the same `wallpaper = monitor,path` lines as hyprpaper.conf.
+    Ok(content
+        .lines()
+        .filter_map(|line| match parse_line(line) {
+            Line::Assignment { key: "wallpaper", value } => WallpaperEntry::parse(&value),
+            _ => None,
+        })
+        .collect())
+}
+
+pub fn save_profile(name: &str, wallpapers: &[WallpaperEntry]) -> Result<(), String> {
+    check_name(name)?;
+    // Animations are saved rather than the still frame hyprpaper shows, so the profile plays them again.
+    let content: String = wallpapers
+        .iter()
+        .map(|entry| {
+            let path = source_path(&entry.path).unwrap_or_else(|| entry.path.clone());
+            format!("wallpaper = {},{}{}\n", entry.monitor, entry.mode.prefix(), escape(&path))
+        })
+        .collect();
+
+    let path = profiles_dir().join(format!("{}.{}", name, PROFILE_EXTENSION));
+    fs::create_dir_all(profiles_dir())
+        .and_then(|_| fs::write(path, content))
+        .map_err(|err| format!("Could not save profile {}: {}", name, err))
+}
+
+pub fn delete_profile(name: &str) -> Result<(), String> {
+    check_name(name)?;
+    let path = profiles_dir().join(format!("{}.{}", name, PROFILE_EXTENSION));
+    fs::remove_file(path).map_err(|err| format!("Could not delete profile {}: {}", name, err))
+}
+
+pub fn apply_profile(name: &str) -> Result<PostApply, String> {
+    let wallpapers = read_profile(name)?;
+    if wallpapers.is_empty() {
+        return Err(format!("Profile {} has no wallpapers", name));
+    }
+    Ok(apply_wallpapers(wallpapers))
+}
+
+// A profile fits when it assigns exactly the connected monitors, profiles for all monitors never match.
+pub fn find_profile_for_monitors(monitors: &[Monitor]) -> Option<String> {
+    list_profiles().into_iter().find(|name| {
+        let Ok(wallpapers) = read_profile(name) else {
+            return false;
+        };
+        let assigned: Vec<&WallpaperEntry> = wallpapers.iter().filter(|entry| !entry.monitor.is_empty()).collect();
+        !assigned.is_empty()
+            && assigned
+                .iter()
+                .all(|entry| monitors.iter().any(|monitor| monitor.matches(&entry.monitor)))
+            && monitors
+                .iter()
+                .all(|monitor| assigned.iter().any(|entry| monitor.matches(&entry.monitor)))
+    })
+}
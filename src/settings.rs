@@ -0,0 +1,265 @@
+use std::fs;
+use std::io::{BufRead, BufReader, Error};
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+
+use crate::hypr::hyprlang::{Line, escape, expand_home, parse_bool, parse_line};
+use crate::hypr::hyprpaper::FitMode;
+use crate::paths::{settings_file, write_atomically};
+
+static SETTINGS: OnceLock<RwLock<Settings>> = OnceLock::new();
+
+const DEFAULT_HOOK_TIMEOUT_SECONDS: u64 = 10;
+pub const DEFAULT_THUMBNAIL_SIZE: i32 = 240;
+pub const MIN_THUMBNAIL_SIZE: i32 = 120;
+pub const MAX_THUMBNAIL_SIZE: i32 = 480;
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum MonitorIdentifier {
+    #[default]
+    Name,
+    Description,
+}
+
+// How GIFs and videos are shown: played by mpvpaper, or as their first frame through hyprpaper alone.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum VideoBackend {
+    #[default]
+    Mpvpaper,
+    Still,
+}
+
+#[derive(Clone)]
+pub struct Settings {
+    pub hooks: Vec<String>,
+    pub hook_timeout_seconds: u64,
+    pub hyprlock_sync: bool,
+    pub hyprlock_config: Option<PathBuf>,
+    pub monitor_identifier: MonitorIdentifier,
+    pub auto_profile: bool,
+    pub video_pause_on_fullscreen: bool,
+    pub video_pause_on_battery: bool,
+    pub video_backend: VideoBackend,
+    pub fit_mode: FitMode,
+    pub thumbnail_size: i32,
+    pub bookmarks: Vec<String>,
+    pub last_directory: Option<String>,
+    pub selected_monitor: Option<String>,
+    pub window_size: Option<(i32, i32)>,
+    pub window_maximized: bool,
+    pub list_view: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            hooks: Vec::new(),
+            hook_timeout_seconds: DEFAULT_HOOK_TIMEOUT_SECONDS,
+            hyprlock_sync: false,
+            hyprlock_config: None,
+            monitor_identifier: MonitorIdentifier::default(),
+            auto_profile: false,
+            video_pause_on_fullscreen: true,
+            video_pause_on_battery: false,
+            video_backend: VideoBackend::default(),
+            fit_mode: FitMode::default(),
+            thumbnail_size: DEFAULT_THUMBNAIL_SIZE,
+            bookmarks: Vec::new(),
+            last_directory: None,
+            selected_monitor: None,
+            window_size: None,
+            window_maximized: false,
+            list_view: false,
+        }
+    }
+}
+
+impl Settings {
+    pub fn new(path: &Path) -> Result<Self, Error> {
+        let file = fs::File::open(path)?;
+        let reader = BufReader::new(file);
+
+        let mut settings = Settings::default();
+
+        for line in reader.lines() {
+            let line = line?;
+
+            let Line::Assignment { key, value: val } = parse_line(&line) else {
+                continue;
+            };
+
+            match key {
+                "hook" if !val.is_empty() => settings.hooks.push(val.to_string()),
+                "hook_timeout" => {
+                    if let Ok(seconds) = val.parse() {
+                        settings.hook_timeout_seconds = seconds;
+                    }
+                }
+                "hyprlock_sync" => {
+                    if let Some(enabled) = parse_bool(&val) {
+                        settings.hyprlock_sync = enabled;
+                    }
+                }
+                "hyprlock_config" if !val.is_empty() => {
+                    settings.hyprlock_config = Some(PathBuf::from(expand_home(&val)));
+                }
+                "monitor_identifier" => match val.as_ref() {
+                    "name" => settings.monitor_identifier = MonitorIdentifier::Name,
+                    "desc" | "description" => settings.monitor_identifier = MonitorIdentifier::Description,
+                    _ => {}
+                },
+                "auto_profile" => {
+                    if let Some(enabled) = parse_bool(&val) {
+                        settings.auto_profile = enabled;
+                    }
+                }
+                "video_pause_on_fullscreen" => {
+                    if let Some(enabled) = parse_bool(&val) {
+                        settings.video_pause_on_fullscreen = enabled;
+                    }
+                }
+                "video_pause_on_battery" => {
+                    if let Some(enabled) = parse_bool(&val) {
+                        settings.video_pause_on_battery = enabled;
+                    }
+                }
+                "video_backend" => match val.as_ref() {
+                    "mpvpaper" => settings.video_backend = VideoBackend::Mpvpaper,
+                    "still" | "none" => settings.video_backend = VideoBackend::Still,
+                    _ => {}
+                },
+                "fit_mode" => {
+                    if let Some(mode) = FitMode::from_name(&val) {
+                        settings.fit_mode = mode;
+                    }
+                }
+                "thumbnail_size" => {
+                    if let Ok(size) = val.parse::<i32>() {
+                        settings.thumbnail_size = size.clamp(MIN_THUMBNAIL_SIZE, MAX_THUMBNAIL_SIZE);
+                    }
+                }
+                "bookmark" if !val.is_empty() => settings.bookmarks.push(expand_home(&val)),
+                "last_directory" if !val.is_empty() => settings.last_directory = Some(expand_home(&val)),
+                "selected_monitor" => settings.selected_monitor = Some(val.to_string()),
+                "window_size" => {
+                    if let Some((width, height)) = val.split_once('x')
+                        && let (Ok(width), Ok(height)) = (width.trim().parse(), height.trim().parse())
+                    {
+                        settings.window_size = Some((width, height));
+                    }
+                }
+                "window_maximized" => {
+                    if let Some(maximized) = parse_bool(&val) {
+                        settings.window_maximized = maximized;
+                    }
+                }
+                "view" => match val.as_ref() {
+                    "grid" => settings.list_view = false,
+                    "list" => settings.list_view = true,
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+
+        Ok(settings)
+    }
+
+    // The keys the Preferences window and the UI write. Hooks and paths stay as written by hand.
+    fn written_values(&self) -> Vec<(&'static str, Vec<String>)> {
+        let flag = |enabled: bool| vec![enabled.to_string()];
+        vec![
+            ("hyprlock_sync", flag(self.hyprlock_sync)),
+            (
+                "monitor_identifier",
+                vec![match self.monitor_identifier {
+                    MonitorIdentifier::Name => "name".to_string(),
+                    MonitorIdentifier::Description => "desc".to_string(),
+                }],
+            ),
+            ("auto_profile", flag(self.auto_profile)),
+            ("video_pause_on_fullscreen", flag(self.video_pause_on_fullscreen)),
+            ("video_pause_on_battery", flag(self.video_pause_on_battery)),
+            (
+                "video_backend",
+                vec![match self.video_backend {
+                    VideoBackend::Mpvpaper => "mpvpaper".to_string(),
+                    VideoBackend::Still => "still".to_string(),
+                }],
+            ),
+            ("fit_mode", vec![self.fit_mode.name().to_string()]),
+            ("thumbnail_size", vec![self.thumbnail_size.to_string()]),
+            ("bookmark", self.bookmarks.clone()),
+            ("last_directory", self.last_directory.iter().cloned().collect()),
+            ("selected_monitor", self.selected_monitor.iter().cloned().collect()),
+            (
+                "window_size",
+                self.window_size
+                    .map(|(width, height)| format!("{}x{}", width, height))
+                    .into_iter()
+                    .collect(),
+            ),
+            ("window_maximized", flag(self.window_maximized)),
+            ("view", vec![if self.list_view { "list" } else { "grid" }.to_string()]),
+        ]
+    }
+
+    // Written keys replace their first line in the file and drop any repeats, new keys are appended.
+    // Comments and every other line are kept as they are.
+    fn save(&self, path: &Path) -> Result<(), Error> {
+        let content = fs::read_to_string(path).unwrap_or_default();
+        let mut written_values = self.written_values();
+
+        let mut lines: Vec<String> = Vec::new();
+        for line in content.lines() {
+            let written = match parse_line(line) {
+                Line::Assignment { key, .. } => written_values.iter_mut().find(|(written_key, _)| *written_key == key),
+                _ => None,
+            };
+            match written {
+                Some((key, values)) => {
+                    lines.extend(values.drain(..).map(|value| format!("{} = {}", key, escape(&value))));
+                }
+                None => lines.push(line.to_string()),
+            }
+        }
+        for (key, values) in written_values {
+            lines.extend(values.iter().map(|value| format!("{} = {}", key, escape(value))));
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_atomically(path, lines.join("\n") + "\n")
+    }
+}
+
+fn get_settings_lock() -> &'static RwLock<Settings> {
+    SETTINGS.get_or_init(|| {
+        let path = settings_file();
+        let settings = if path.exists() {
+            Settings::new(&path).unwrap_or_else(|err| {
+                eprintln!("Error loading {}: {}", path.display(), err);
+                Settings::default()
+            })
+        } else {
+            Settings::default()
+        };
+        RwLock::new(settings)
+    })
+}
+
+pub fn get_settings() -> Settings {
+    get_settings_lock().read().unwrap().clone()
+}
+
+pub fn update_settings(update: impl FnOnce(&mut Settings)) {
+    let settings = {
+        let mut settings = get_settings_lock().write().unwrap();
+        update(&mut settings);
+        settings.clone()
+    };
+    if let Err(err) = settings.save(&settings_file()) {
+        eprintln!("Error saving {}: {}", settings_file().display(), err);
+    }
+}
@@ -1,139 +1,193 @@
-use std::{path::Path, sync::{OnceLock, RwLock}};
+use std::path::Path;
 
+use crate::app_model::app_model;
+use crate::hypr::hyprctl::Monitor;
+use crate::hypr::hyprlang::Diagnostic;
+use crate::hypr::mpvpaper::frame_path;
 use crate::hypr::hyprpaper::{HyprpaperConfig, WallpaperEntry};
+use crate::images::transcode::transcoded_path;
+use crate::settings::{MonitorIdentifier, get_settings};
 
-static APP_STATE: OnceLock<RwLock<AppState>> = OnceLock::new();
-
-#[derive(Default)]
-pub struct AppState {
-    pub config: Option<HyprpaperConfig>,
-    pub monitors: Vec<String>,
-    selected_monitor: String,
+pub fn set_config(config: HyprpaperConfig) {
+    app_model().set_config(config);
 }
 
-fn get_app_state() -> &'static RwLock<AppState> {
-    APP_STATE.get_or_init(|| RwLock::new(AppState::default()))
+pub fn set_wallpapers(wallpapers: Vec<WallpaperEntry>) {
+    app_model().update_config(|config| config.wallpapers = wallpapers);
+}
+
+pub fn get_wallpapers() -> Vec<WallpaperEntry> {
+    app_model().read_config(|config| config.wallpapers.clone())
+}
+
+pub fn get_monitors_showing(path: &str, wallpapers: &[WallpaperEntry]) -> Vec<String> {
+    let monitors = app_model().monitors();
+    // Images hyprpaper can't decode are shown through a PNG copy, animations through a still frame.
+    let transcoded = transcoded_path(path);
+    let frame = frame_path(path);
+    let mut showing = Vec::new();
+    for entry in wallpapers {
+        if entry.path != path && Path::new(&entry.path) != transcoded && Path::new(&entry.path) != frame {
+            continue;
+        }
+        if entry.monitor.is_empty() {
+            showing.extend(monitors.iter().map(|monitor| monitor.name.clone()));
+        } else {
+            showing.push(resolve_monitor_name(&monitors, &entry.monitor));
+        }
+    }
+    showing.sort();
+    showing.dedup();
+    showing
 }
 
-pub fn set_config(config: HyprpaperConfig) {
-    let mut state = get_app_state().write().unwrap();
-    state.config = Some(config);
+// Entries may name a monitor by connector or by `desc:`, for display they are shown by connector.
+fn resolve_monitor_name(monitors: &[Monitor], identifier: &str) -> String {
+    monitors
+        .iter()
+        .find(|monitor| monitor.matches(identifier))
+        .map(|monitor| monitor.name.clone())
+        .unwrap_or_else(|| identifier.to_string())
 }
 
-pub fn set_wallpapers(wallpapers: Vec<WallpaperEntry>) {
-    let mut state = get_app_state().write().unwrap();
-    state.config.as_mut().unwrap().wallpapers = wallpapers;
+fn is_same_monitor(monitors: &[Monitor], name: &str, identifier: &str) -> bool {
+    name == identifier || (!name.is_empty() && resolve_monitor_name(monitors, identifier) == name)
 }
 
 pub fn remove_wallpaper_of_monitor(monitor: String) {
-    let mut state = get_app_state().write().unwrap();
-    state
-        .config
-        .as_mut()
-        .unwrap()
-        .wallpapers
-        .retain(|entry| !entry.monitor.eq(&monitor));
+    let monitors = app_model().monitors();
+    app_model().update_config(|config| {
+        config
+            .wallpapers
+            .retain(|entry| !is_same_monitor(&monitors, &monitor, &entry.monitor))
+    });
+}
+
+pub fn get_monitor_identifier(name: &str) -> String {
+    app_model().with_monitors(|monitors| match monitors.iter().find(|monitor| monitor.name == name) {
+        Some(monitor)
+            if get_settings().monitor_identifier == MonitorIdentifier::Description
+                && !monitor.description.is_empty() =>
+        {
+            monitor.description_identifier()
+        }
+        _ => name.to_string(),
+    })
+}
+
+// Rewrites entries to the form the settings ask for, connector names or `desc:`, and returns
+// whether anything changed. Entries already in that form are left as written.
+pub fn migrate_monitor_identifiers() -> bool {
+    let monitors = app_model().monitors();
+    let use_description = get_settings().monitor_identifier == MonitorIdentifier::Description;
+    let mut changed = false;
+    app_model().update_config(|config| {
+        for entry in config.wallpapers.iter_mut() {
+            if entry.monitor.starts_with("desc:") == use_description {
+                continue;
+            }
+            let Some(monitor) = monitors.iter().find(|monitor| monitor.matches(&entry.monitor)) else {
+                continue;
+            };
+            let identifier = if use_description && !monitor.description.is_empty() {
+                monitor.description_identifier()
+            } else {
+                monitor.name.clone()
+            };
+            if entry.monitor != identifier {
+                entry.monitor = identifier;
+                changed = true;
+            }
+        }
+    });
+    changed
 }
 
-// pub fn set_preloads(preloads: Vec<String>) {
-//     let mut state = get_app_state().write().unwrap();
-//     state.config.as_mut().unwrap().preloads = preloads;
-// }
-
 pub fn set_preloads_from_wallpapers() {
-    let mut state = get_app_state().write().unwrap();
-    state.config.as_mut().unwrap().preloads = vec![];
-    let wallpapers = state.config.as_ref().unwrap().wallpapers.clone();
-    for wallpaper in wallpapers {
-        state
-            .config
-            .as_mut()
-            .unwrap()
-            .preloads
-            .append(&mut vec![wallpaper.path.clone()]);
-    }
+    app_model().update_config(|config| {
+        config.preloads = config.wallpapers.iter().map(|wallpaper| wallpaper.path.clone()).collect();
+    });
 }
 
 pub fn add_wallpaper(entry: WallpaperEntry) {
-    let mut state = get_app_state().write().unwrap();
-    state
-        .config
-        .as_mut()
-        .unwrap()
-        .wallpapers
-        .append(&mut vec![entry]);
+    app_model().update_config(|config| config.wallpapers.push(entry));
 }
 
-pub fn set_monitors(monitors: Vec<String>) {
-    let mut state = get_app_state().write().unwrap();
-    state.monitors = monitors;
+pub fn set_monitors(monitors: Vec<Monitor>) {
+    app_model().set_monitors(monitors);
 }
 
 pub fn get_monitors() -> Vec<String> {
-    let state = get_app_state().read().unwrap();
-    state.monitors.clone()
+    app_model().with_monitors(|monitors| monitors.iter().map(|monitor| monitor.name.clone()).collect())
+}
+
+pub fn get_monitor_details() -> Vec<Monitor> {
+    app_model().monitors()
 }
 
 pub fn has_more_than_one_monitors() -> bool {
-    let state = get_app_state().read().unwrap();
-    state.monitors.len() > 1
+    app_model().with_monitors(|monitors| monitors.len() > 1)
 }
 
 pub fn has_more_than_one_wallpaper() -> bool {
-    let state = get_app_state().read().unwrap();
-    state.config.as_ref().unwrap().wallpapers.len() > 1
+    app_model().read_config(|config| config.wallpapers.len() > 1)
 }
+
 pub fn has_more_wallpapers_than_monitors() -> bool {
-    let state = get_app_state().read().unwrap();
-    state.config.as_ref().unwrap().wallpapers.len() > state.monitors.len()
+    let monitor_count = app_model().with_monitors(|monitors| monitors.len());
+    app_model().read_config(|config| config.wallpapers.len() > monitor_count)
 }
 
 pub fn has_wallpapers() -> bool {
-    let state = get_app_state().read().unwrap();
-    state.config.as_ref().unwrap().wallpapers.len() > 0
+    app_model().read_config(|config| !config.wallpapers.is_empty())
 }
 
 pub fn get_first_wallpaper_path() -> String {
-    let state = get_app_state().read().unwrap();
-    Path::new(&state.config.as_ref().unwrap().wallpapers.first().unwrap().path)
-    .parent()
-    .map(|p| p.to_string_lossy().to_string())
-    .unwrap_or_else(|| "/".to_string())
+    app_model()
+        .read_config(|config| {
+            config
+                .wallpapers
+                .first()
+                .and_then(|entry| Path::new(&entry.path).parent().map(|parent| parent.to_string_lossy().to_string()))
+        })
+        .unwrap_or_else(|| "/".to_string())
 }
 
 pub fn has_empty_monitor_name() -> bool {
-    let state = get_app_state().read().unwrap();
-    state
-        .config
-        .as_ref()
-        .unwrap()
-        .wallpapers
-        .iter()
-        .any(|e| e.monitor.is_empty())
+    app_model().read_config(|config| config.wallpapers.iter().any(|e| e.monitor.is_empty()))
 }
 
 pub fn has_monitor(monitor: String) -> bool {
-    let state = get_app_state().read().unwrap();
-    state
-        .config
-        .as_ref()
-        .unwrap()
-        .wallpapers
-        .iter()
-        .any(|e| e.monitor.eq(&monitor))
+    let monitors = app_model().monitors();
+    app_model().read_config(|config| {
+        config
+            .wallpapers
+            .iter()
+            .any(|e| is_same_monitor(&monitors, &monitor, &e.monitor))
+    })
 }
 
 pub fn save_config() {
-    let state = get_app_state().read().unwrap();
-    state.config.as_ref().unwrap().save_config();
+    app_model().save_config();
+}
+
+pub fn get_config_diagnostics() -> Vec<Diagnostic> {
+    let monitors = app_model().monitors();
+    app_model().read_config(|config| config.validate(Some(&monitors)))
 }
 
 pub fn set_selected_monitor(monitor: String) {
-    let mut state = get_app_state().write().unwrap();
-    state.selected_monitor = monitor;
+    app_model().set_selected_monitor(monitor);
 }
 
 pub fn get_selected_monitor() -> String {
-    let state = get_app_state().read().unwrap();
-    state.selected_monitor.clone()
+    app_model().selected_monitor()
+}
+
+pub fn set_preview(preview: Option<WallpaperEntry>) {
+    app_model().set_preview(preview);
+}
+
+pub fn get_preview() -> Option<WallpaperEntry> {
+    app_model().preview()
 }
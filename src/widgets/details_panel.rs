@@ -0,0 +1,181 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use gio::glib::{self, clone};
+use gtk::prelude::*;
+use gtk::{Align, Box, Grid, Label, Orientation, ScrolledWindow};
+
+use crate::app_model::app_model;
+use crate::images::exif::Exif;
+use crate::images::fit::{Fit, aspect_ratio_label};
+use crate::images::image_info::ImageInfo;
+use crate::images::wallpaper_item::WallpaperItem;
+use crate::state::get_monitor_details;
+
+#[derive(Clone)]
+pub struct DetailsPanel {
+    pub widget: ScrolledWindow,
+    content: Box,
+    item: Rc<RefCell<Option<WallpaperItem>>>,
+    // Every change bumps it, details read for an image no longer selected are dropped.
+    request: Rc<Cell<u64>>,
+}
+
+impl DetailsPanel {
+    pub fn new() -> Self {
+        let content = Box::builder()
+            .orientation(Orientation::Vertical)
+            .spacing(12)
+            .margin_top(8)
+            .margin_bottom(8)
+            .margin_start(12)
+            .margin_end(8)
+            .build();
+
+        let widget = ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .width_request(280)
+            .child(&content)
+            .build();
+        widget.add_css_class("details-panel");
+
+        let panel = Self {
+            widget,
+            content,
+            item: Rc::new(RefCell::new(None)),
+            request: Rc::new(Cell::new(0)),
+        };
+        panel.show(None);
+
+        // The fit lines depend on the monitors, so they are redone when one is added, removed or changed.
+        app_model().connect_monitors_changed(clone!(
+            #[strong]
+            panel,
+            move |_| {
+                let item = panel.item.borrow().clone();
+                panel.show(item.as_ref());
+            }
+        ));
+        panel
+    }
+
+    pub fn show(&self, item: Option<&WallpaperItem>) {
+        self.item.replace(item.cloned());
+        let request = self.request.get() + 1;
+        self.request.set(request);
+        while let Some(child) = self.content.first_child() {
+            self.content.remove(&child);
+        }
+
+        let Some(item) = item else {
+            let placeholder = Label::builder().label("No image selected").build();
+            placeholder.add_css_class("dim-label");
+            self.content.append(&placeholder);
+            return;
+        };
+
+        let title = Label::builder()
+            .label(item.name())
+            .halign(Align::Start)
+            .wrap(true)
+            .build();
+        title.add_css_class("heading");
+        self.content.append(&title);
+
+        // The header, unless the folder scan already read it, and the EXIF data are read off the UI thread.
+        let path = item.path();
+        let needs_info = !item.info_loaded();
+        glib::spawn_future_local(clone!(
+            #[strong(rename_to = panel)]
+            self,
+            #[strong]
+            item,
+            async move {
+                let read = move || (needs_info.then(|| ImageInfo::read(&path)), Exif::read(&path));
+                let Ok((info, exif)) = gio::spawn_blocking(read).await else {
+                    return;
+                };
+                if panel.request.get() != request {
+                    return;
+                }
+                if let Some(info) = info {
+                    item.set_info(info);
+                }
+                panel.show_details(&item, exif);
+            }
+        ));
+    }
+
+    fn show_details(&self, item: &WallpaperItem, exif: Option<Exif>) {
+        let mut rows = Vec::new();
+        let has_info = item.has_info();
+        let (width, height) = (item.width(), item.height());
+        if has_info && width > 0 {
+            rows.push(("Resolution", format!("{} × {}", width, height)));
+            rows.push(("Aspect ratio", aspect_ratio_label(width, height)));
+        }
+        if has_info {
+            rows.push(("File size", glib::format_size(item.size()).to_string()));
+            rows.push(("Format", item.format().to_uppercase()));
+        }
+        if let Some(exif) = exif {
+            for (name, value) in [
+                ("Camera", exif.camera),
+                ("Lens", exif.lens),
+                ("Taken", exif.taken),
+                ("Exposure", exif.exposure),
+            ] {
+                if let Some(value) = value {
+                    rows.push((name, value));
+                }
+            }
+        }
+        self.content.append(&details_grid(&rows));
+
+        if has_info && width > 0 {
+            let fit_box = Box::builder()
+                .orientation(Orientation::Vertical)
+                .spacing(4)
+                .build();
+            for monitor in get_monitor_details() {
+                let Some(fit) = Fit::new(width, height, monitor.width, monitor.height) else {
+                    continue;
+                };
+                let label = Label::builder()
+                    .label(format!("{} is {} — {}", monitor.name, monitor.describe_size(), fit.describe()))
+                    .halign(Align::Start)
+                    .xalign(0.0)
+                    .wrap(true)
+                    .build();
+                if fit.needs_warning() {
+                    label.add_css_class("warning");
+                }
+                fit_box.append(&label);
+            }
+            self.content.append(&fit_box);
+        }
+    }
+}
+
+fn details_grid(rows: &[(&str, String)]) -> Grid {
+    let grid = Grid::builder().row_spacing(6).column_spacing(12).build();
+    for (row, (name, value)) in rows.iter().enumerate() {
+        let name_label = Label::builder().label(*name).halign(Align::End).valign(Align::Start).build();
+        name_label.add_css_class("dim-label");
+        grid.attach(&name_label, 0, row as i32, 1, 1);
+        grid.attach(
+            &Label::builder()
+                .label(value)
+                .halign(Align::Start)
+                .xalign(0.0)
+                .selectable(true)
+                .wrap(true)
+                .build(),
+            1,
+            row as i32,
+            1,
+            1,
+        );
+    }
+    grid
+}
@@ -0,0 +1,89 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::{Box, Button, Image, Label, Orientation};
+
+use crate::app_model::app_model;
+use crate::state::get_config_diagnostics;
+
+#[derive(Clone)]
+pub struct DiagnosticsBanner {
+    pub widget: Box,
+    label: Label,
+    details: Rc<RefCell<Vec<String>>>,
+}
+
+impl DiagnosticsBanner {
+    pub fn new() -> Self {
+        let banner_box = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(8)
+            .visible(false)
+            .build();
+        banner_box.add_css_class("warning-banner");
+
+        banner_box.append(
+            &Image::builder()
+                .icon_name("dialog-warning-symbolic")
+                .valign(gtk::Align::Start)
+                .build(),
+        );
+
+        let label = Label::builder()
+            .halign(gtk::Align::Start)
+            .hexpand(true)
+            .wrap(true)
+            .selectable(true)
+            .build();
+        banner_box.append(&label);
+
+        let close_button = Button::builder()
+            .icon_name("window-close-symbolic")
+            .valign(gtk::Align::Start)
+            .tooltip_text("Dismiss")
+            .build();
+        close_button.add_css_class("flat");
+        let banner = banner_box.clone();
+        close_button.connect_clicked(move |_| banner.set_visible(false));
+        banner_box.append(&close_button);
+
+        let diagnostics_banner = Self {
+            widget: banner_box,
+            label,
+            details: Rc::new(RefCell::new(Vec::new())),
+        };
+        diagnostics_banner.update();
+
+        // The config is reloaded when it is edited, fixed problems go away and new ones show up.
+        app_model().connect_wallpaper_changed(glib::clone!(
+            #[strong]
+            diagnostics_banner,
+            move |_| diagnostics_banner.update()
+        ));
+        app_model().connect_monitors_changed(glib::clone!(
+            #[strong]
+            diagnostics_banner,
+            move |_| diagnostics_banner.update()
+        ));
+
+        diagnostics_banner
+    }
+
+    // A dismissed banner only comes back when the problems change.
+    fn update(&self) {
+        let details: Vec<String> = get_config_diagnostics().iter().map(|diagnostic| diagnostic.to_string()).collect();
+        if *self.details.borrow() == details {
+            return;
+        }
+
+        let title = match details.len() {
+            1 => "The hyprpaper config has a problem:".to_string(),
+            count => format!("The hyprpaper config has {} problems:", count),
+        };
+        self.label.set_label(&format!("{}\n{}", title, details.join("\n")));
+        self.widget.set_visible(!details.is_empty());
+        self.details.replace(details);
+    }
+}
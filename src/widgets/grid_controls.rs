@@ -0,0 +1,152 @@
+use std::cell::Cell;
+
+use gtk::gdk::ModifierType;
+use gtk::glib::Propagation;
+use gtk::prelude::*;
+use gtk::{Adjustment, Box, DropDown, EventControllerScroll, EventControllerScrollFlags, Orientation, PropagationPhase, Scale, SearchEntry, ToggleButton};
+
+use crate::app_model::app_model;
+use crate::images::filter::{self, ImageSort};
+use crate::settings::{MAX_THUMBNAIL_SIZE, MIN_THUMBNAIL_SIZE, get_settings, update_settings};
+use crate::widgets::image_card::update_thumbnail_size;
+use crate::widgets::images_grid_view::ImagesGridView;
+
+const ZOOM_STEP: f64 = 20.0;
+
+pub struct GridControls {
+    pub widget: Box,
+    pub search_entry: SearchEntry,
+}
+
+impl GridControls {
+    pub fn new(images_grid_view: &ImagesGridView) -> Self {
+        let controls_box = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(5)
+            .build();
+        controls_box.add_css_class("grid-controls");
+
+        let search_entry = SearchEntry::builder()
+            .placeholder_text("Search images")
+            .hexpand(true)
+            .build();
+        search_entry.connect_search_changed(with_grid(images_grid_view, |grid, entry: &SearchEntry| {
+            let query = entry.text().to_string();
+            grid.update_filter(|image_filter| image_filter.query = query);
+        }));
+        controls_box.append(&search_entry);
+
+        let orientation_drop_down = DropDown::from_strings(&filter::Orientation::LABELS);
+        orientation_drop_down.connect_selected_notify(with_grid(images_grid_view, |grid, drop_down: &DropDown| {
+            let orientation = filter::Orientation::from_index(drop_down.selected());
+            grid.update_filter(|image_filter| image_filter.orientation = orientation);
+        }));
+        controls_box.append(&orientation_drop_down);
+
+        let resolution_drop_down = DropDown::from_strings(&filter::MIN_RESOLUTION_LABELS);
+        resolution_drop_down.connect_selected_notify(with_grid(images_grid_view, |grid, drop_down: &DropDown| {
+            let index = drop_down.selected();
+            grid.update_filter(|image_filter| image_filter.set_min_resolution_index(index));
+        }));
+        controls_box.append(&resolution_drop_down);
+
+        let aspect_ratio_drop_down = DropDown::from_strings(&filter::ASPECT_RATIO_LABELS);
+        aspect_ratio_drop_down.connect_selected_notify(with_grid(images_grid_view, |grid, drop_down: &DropDown| {
+            let index = drop_down.selected();
+            grid.update_filter(|image_filter| image_filter.set_aspect_ratio_index(index));
+        }));
+        controls_box.append(&aspect_ratio_drop_down);
+
+        let format_drop_down = DropDown::from_strings(&filter::FORMAT_LABELS);
+        format_drop_down.connect_selected_notify(with_grid(images_grid_view, |grid, drop_down: &DropDown| {
+            let index = drop_down.selected();
+            grid.update_filter(|image_filter| image_filter.set_format_index(index));
+        }));
+        controls_box.append(&format_drop_down);
+
+        let sort_drop_down = DropDown::from_strings(&ImageSort::LABELS);
+        let descending_toggle_button = ToggleButton::builder()
+            .icon_name("view-sort-descending-symbolic")
+            .tooltip_text("Descending")
+            .build();
+
+        let grid = images_grid_view.clone();
+        let descending = descending_toggle_button.clone();
+        sort_drop_down.connect_selected_notify(move |drop_down| {
+            grid.set_sort(ImageSort::from_index(drop_down.selected()), descending.is_active());
+        });
+        let grid = images_grid_view.clone();
+        let sort = sort_drop_down.clone();
+        descending_toggle_button.connect_toggled(move |toggle_button| {
+            grid.set_sort(ImageSort::from_index(sort.selected()), toggle_button.is_active());
+        });
+        controls_box.append(&sort_drop_down);
+        controls_box.append(&descending_toggle_button);
+
+        let zoom_scale = Scale::with_range(Orientation::Horizontal, MIN_THUMBNAIL_SIZE as f64, MAX_THUMBNAIL_SIZE as f64, ZOOM_STEP);
+        zoom_scale.set_draw_value(false);
+        zoom_scale.set_width_request(120);
+        zoom_scale.set_tooltip_text(Some("Thumbnail Size (Ctrl+Scroll)"));
+        bind_thumbnail_size(&zoom_scale.adjustment());
+        controls_box.append(&zoom_scale);
+
+        // Captured before the scrolled window sees it, so Ctrl+Scroll zooms instead of scrolling.
+        let scroll_controller = EventControllerScroll::new(EventControllerScrollFlags::VERTICAL);
+        scroll_controller.set_propagation_phase(PropagationPhase::Capture);
+        // Touchpads scroll in small fractions, they are added up to whole steps.
+        let scale = zoom_scale.clone();
+        let scrolled = Cell::new(0.0);
+        scroll_controller.connect_scroll(move |controller, _, dy| {
+            if !controller.current_event_state().contains(ModifierType::CONTROL_MASK) {
+                return Propagation::Proceed;
+            }
+            let total = scrolled.get() + dy;
+            let steps = total.trunc();
+            scrolled.set(total - steps);
+            if steps != 0.0 {
+                scale.set_value(scale.value() - steps * ZOOM_STEP);
+            }
+            Propagation::Stop
+        });
+        images_grid_view.widget.add_controller(scroll_controller);
+
+        // Cards follow the shape of the monitor being picked for.
+        app_model().connect_thumbnail_size_notify(|_| update_thumbnail_size());
+        app_model().connect_selected_monitor_changed(|_, _| update_thumbnail_size());
+        app_model().connect_monitors_changed(|_| update_thumbnail_size());
+
+        let list_toggle_button = ToggleButton::builder()
+            .icon_name("view-list-symbolic")
+            .tooltip_text("List View")
+            .active(get_settings().list_view)
+            .build();
+        images_grid_view.set_list_mode(list_toggle_button.is_active());
+        list_toggle_button.connect_toggled(with_grid(images_grid_view, |grid, toggle_button: &ToggleButton| {
+            let list_view = toggle_button.is_active();
+            grid.set_list_mode(list_view);
+            update_settings(|settings| settings.list_view = list_view);
+        }));
+        controls_box.append(&list_toggle_button);
+
+        Self {
+            widget: controls_box,
+            search_entry,
+        }
+    }
+}
+
+fn with_grid<W>(images_grid_view: &ImagesGridView, handler: impl Fn(&ImagesGridView, &W) + 'static) -> impl Fn(&W) + 'static {
+    let grid = images_grid_view.clone();
+    move |widget| handler(&grid, widget)
+}
+
+// The preferences and the zoom slider change the same size, either one follows the other.
+pub fn bind_thumbnail_size(adjustment: &Adjustment) {
+    app_model()
+        .bind_property("thumbnail-size", adjustment, "value")
+        .transform_to(|_, size: i32| Some(size as f64))
+        .transform_from(|_, value: f64| Some(value.round() as i32))
+        .bidirectional()
+        .sync_create()
+        .build();
+}
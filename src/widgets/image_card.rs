@@ -1,20 +1,74 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
 use gio::File;
+use gtk::glib::{self, Binding, SignalHandlerId};
 use gtk::prelude::*;
-use gtk::{Align, Box, ContentFit, GestureClick, Orientation, Picture};
+use gtk::gdk::{BUTTON_SECONDARY, Display, Paintable, Rectangle};
+use gtk::{
+    Align, Box, ContentFit, CssProvider, GestureClick, Image, Label, MediaFile, MediaStream, Orientation, Overlay, Picture, PopoverMenu
+};
+
+use crate::images::wallpaper_item::WallpaperItem;
+use crate::settings::get_settings;
+use crate::state::{get_monitor_details, get_selected_monitor};
+use crate::windows::image_actions::image_menu;
+
+const DEFAULT_ASPECT_RATIO: f64 = 16.0 / 9.0;
+
+thread_local! {
+    static THUMBNAIL_SIZE_PROVIDER: CssProvider = {
+        let provider = CssProvider::new();
+        if let Some(display) = Display::default() {
+            gtk::style_context_add_provider_for_display(&display, &provider, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
+        }
+        provider
+    };
+}
+
+// Cards are sized through CSS, so a new size reaches every card already on screen. They take the
+// shape of the monitor the images are picked for, the thumbnail size being the longer side.
+pub fn update_thumbnail_size() {
+    let size = get_settings().thumbnail_size;
+    let selected_monitor = get_selected_monitor();
+    let monitors = get_monitor_details();
+    let aspect_ratio = monitors
+        .iter()
+        .find(|monitor| monitor.name == selected_monitor)
+        .or(monitors.first())
+        .filter(|monitor| monitor.width > 0 && monitor.height > 0)
+        .map(|monitor| monitor.width as f64 / monitor.height as f64)
+        .unwrap_or(DEFAULT_ASPECT_RATIO);
+    let (width, height) = if aspect_ratio >= 1.0 {
+        (size, (size as f64 / aspect_ratio).round() as i32)
+    } else {
+        ((size as f64 * aspect_ratio).round() as i32, size)
+    };
+
+    THUMBNAIL_SIZE_PROVIDER.with(|provider| {
+        provider.load_from_string(&format!(
+            ".image-frame, .image-thumb {{ min-width: {}px; min-height: {}px; }}",
+            width, height
+        ));
+    });
+}
 
 pub struct ImageCard {
     pub widget: Box,
     picture: Picture,
+    badges_box: Box,
+    favorite_image: Image,
+    item: Rc<RefCell<Option<WallpaperItem>>>,
+    bindings: RefCell<Vec<Binding>>,
+    handlers: RefCell<Vec<SignalHandlerId>>,
 }
 
 impl ImageCard {
-    pub fn new() -> Self {
+    pub fn new(on_click: Rc<dyn Fn(&str)>) -> Self {
         let frame = Box::builder()
             .orientation(Orientation::Vertical)
             .vexpand(false)
             .hexpand(false)
-            .width_request(240)
-            .height_request(140)
             .spacing(0)
             .build();
         frame.add_css_class("image-frame");
@@ -25,33 +79,147 @@ impl ImageCard {
             .vexpand(false)
             .hexpand(false)
             .content_fit(ContentFit::Contain)
-            .width_request(240)
-            .height_request(140)
             .build();
         picture.add_css_class("image-thumb");
 
-        frame.append(&picture);
+        let badges_box = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .halign(Align::End)
+            .valign(Align::Start)
+            .spacing(4)
+            .build();
+        badges_box.add_css_class("monitor-badges");
+
+        let favorite_image = Image::builder()
+            .icon_name("starred-symbolic")
+            .halign(Align::Start)
+            .valign(Align::Start)
+            .visible(false)
+            .build();
+        favorite_image.add_css_class("favorite-star");
+
+        let overlay = Overlay::builder().child(&picture).build();
+        overlay.add_overlay(&badges_box);
+        overlay.add_overlay(&favorite_image);
+
+        frame.append(&overlay);
+
+        let item: Rc<RefCell<Option<WallpaperItem>>> = Rc::new(RefCell::new(None));
+
+        let controller = GestureClick::new();
+        controller.connect_pressed(glib::clone!(
+            #[strong]
+            item,
+            move |_gesture, n_press, _x, _y| {
+                if n_press != 1 {
+                    return;
+                }
+                if let Some(item) = item.borrow().as_ref() {
+                    on_click(&item.path());
+                }
+            }
+        ));
+        picture.add_controller(controller);
+
+        let popover_menu = PopoverMenu::builder().has_arrow(false).build();
+        popover_menu.set_parent(&frame);
+        frame.connect_destroy(glib::clone!(
+            #[weak]
+            popover_menu,
+            move |_| {
+                popover_menu.unparent();
+            }
+        ));
+
+        let context_controller = GestureClick::builder().button(BUTTON_SECONDARY).build();
+        context_controller.connect_pressed(glib::clone!(
+            #[strong]
+            item,
+            move |_gesture, _n_press, x, y| {
+                if let Some(item) = item.borrow().as_ref() {
+                    popover_menu.set_menu_model(Some(&image_menu(item)));
+                    popover_menu.set_pointing_to(Some(&Rectangle::new(x as i32, y as i32, 1, 1)));
+                    popover_menu.popup();
+                }
+            }
+        ));
+        frame.add_controller(context_controller);
 
         Self {
             widget: frame,
-            picture: picture,
+            picture,
+            badges_box,
+            favorite_image,
+            item,
+            bindings: RefCell::new(Vec::new()),
+            handlers: RefCell::new(Vec::new()),
         }
     }
 
-    pub fn set_image(&self, path: String, on_click: fn(path: &str)) {
-        let image_file = File::for_path(&path);
-        self.picture.set_file(Some(&image_file));
+    pub fn bind(&self, item: &WallpaperItem) {
+        self.unbind();
 
-        let mut parts: Vec<&str> = path.split('/').collect();
+        let mut bindings = self.bindings.borrow_mut();
+        show_picture(&self.picture, item);
+        bindings.push(
+            item.bind_property("name", &self.picture, "tooltip-text")
+                .sync_create()
+                .build(),
+        );
+        bindings.push(
+            item.bind_property("favorite", &self.favorite_image, "visible")
+                .sync_create()
+                .build(),
+        );
+        self.item.replace(Some(item.clone()));
 
-        self.picture.set_tooltip_text(parts.pop());
+        let mut handlers = self.handlers.borrow_mut();
+        let picture = self.picture.clone();
+        handlers.push(item.connect_animated_notify(move |item| show_picture(&picture, item)));
+        set_badges(&self.badges_box, &item.active_monitors());
+        let badges_box = self.badges_box.clone();
+        handlers.push(item.connect_active_monitors_notify(move |item| {
+            set_badges(&badges_box, &item.active_monitors());
+        }));
+    }
 
-        let controller = GestureClick::new();
+    pub fn unbind(&self) {
+        for binding in self.bindings.borrow_mut().drain(..) {
+            binding.unbind();
+        }
+        if let Some(media_stream) = self.picture.paintable().and_downcast::<MediaStream>() {
+            media_stream.pause();
+            self.picture.set_paintable(None::<&Paintable>);
+        }
+        if let Some(item) = self.item.take() {
+            for handler in self.handlers.borrow_mut().drain(..) {
+                item.disconnect(handler);
+            }
+        }
+    }
+}
 
-        controller.connect_pressed(move |_gesture, _n_press, _x, _y| {
-            on_click(&path);
-        });
+fn show_picture(picture: &Picture, item: &WallpaperItem) {
+    if item.animated() {
+        // Muted and looping, only cards in view are bound so only those play.
+        let media_file = MediaFile::for_filename(item.path());
+        media_file.set_muted(true);
+        media_file.set_loop(true);
+        media_file.play();
+        picture.set_paintable(Some(&media_file));
+    } else {
+        picture.set_file(Some(&File::for_path(item.path())));
+    }
+}
+
+pub fn set_badges(badges_box: &Box, monitors: &[String]) {
+    while let Some(child) = badges_box.first_child() {
+        badges_box.remove(&child);
+    }
 
-        self.picture.add_controller(controller);
+    for monitor in monitors {
+        let badge = Label::builder().label(monitor).build();
+        badge.add_css_class("monitor-badge");
+        badges_box.append(&badge);
     }
 }
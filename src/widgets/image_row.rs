@@ -0,0 +1,133 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gio::File;
+use gtk::glib::{self, SignalHandlerId};
+use gtk::prelude::*;
+use gtk::gdk::Paintable;
+use gtk::{Align, Box, ContentFit, GestureClick, Label, Orientation, Picture};
+
+use crate::images::wallpaper_item::WallpaperItem;
+use crate::widgets::image_card::set_badges;
+
+// The compact alternative to ImageCard: a small thumbnail, the file name and the dimensions.
+pub struct ImageRow {
+    pub widget: Box,
+    picture: Picture,
+    name_label: Label,
+    size_label: Label,
+    badges_box: Box,
+    item: Rc<RefCell<Option<WallpaperItem>>>,
+    handlers: RefCell<Vec<SignalHandlerId>>,
+}
+
+impl ImageRow {
+    pub fn new(on_click: Rc<dyn Fn(&str)>) -> Self {
+        let row = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(12)
+            .build();
+        row.add_css_class("image-row");
+
+        let picture = Picture::builder()
+            .content_fit(ContentFit::Cover)
+            .width_request(64)
+            .height_request(36)
+            .build();
+        picture.add_css_class("image-row-thumb");
+        row.append(&picture);
+
+        let name_label = Label::builder()
+            .halign(Align::Start)
+            .hexpand(true)
+            .ellipsize(gtk::pango::EllipsizeMode::Middle)
+            .build();
+        row.append(&name_label);
+
+        let badges_box = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(4)
+            .valign(Align::Center)
+            .build();
+        badges_box.add_css_class("monitor-badges");
+        row.append(&badges_box);
+
+        let size_label = Label::builder().width_chars(11).xalign(1.0).build();
+        size_label.add_css_class("dim-label");
+        row.append(&size_label);
+
+        let item: Rc<RefCell<Option<WallpaperItem>>> = Rc::new(RefCell::new(None));
+
+        let controller = GestureClick::new();
+        controller.connect_pressed(glib::clone!(
+            #[strong]
+            item,
+            move |_gesture, n_press, _x, _y| {
+                if n_press != 1 {
+                    return;
+                }
+                if let Some(item) = item.borrow().as_ref() {
+                    on_click(&item.path());
+                }
+            }
+        ));
+        row.add_controller(controller);
+
+        Self {
+            widget: row,
+            picture,
+            name_label,
+            size_label,
+            badges_box,
+            item,
+            handlers: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub fn bind(&self, item: &WallpaperItem) {
+        self.unbind();
+
+        show_picture(&self.picture, item);
+        self.name_label.set_label(&item.name());
+        self.name_label.set_tooltip_text(Some(&item.path()));
+        set_size_label(&self.size_label, item);
+        set_badges(&self.badges_box, &item.active_monitors());
+
+        let mut handlers = self.handlers.borrow_mut();
+        let picture = self.picture.clone();
+        handlers.push(item.connect_video_notify(move |item| show_picture(&picture, item)));
+        let size_label = self.size_label.clone();
+        handlers.push(item.connect_width_notify(move |item| set_size_label(&size_label, item)));
+        let badges_box = self.badges_box.clone();
+        handlers.push(item.connect_active_monitors_notify(move |item| {
+            set_badges(&badges_box, &item.active_monitors());
+        }));
+        self.item.replace(Some(item.clone()));
+    }
+
+    pub fn unbind(&self) {
+        self.picture.set_paintable(None::<&Paintable>);
+        if let Some(item) = self.item.take() {
+            for handler in self.handlers.borrow_mut().drain(..) {
+                item.disconnect(handler);
+            }
+        }
+    }
+}
+
+// Animations show their first frame, videos have nothing gdk-pixbuf can draw.
+fn show_picture(picture: &Picture, item: &WallpaperItem) {
+    if item.video() {
+        picture.set_paintable(None::<&Paintable>);
+    } else {
+        picture.set_file(Some(&File::for_path(item.path())));
+    }
+}
+
+fn set_size_label(size_label: &Label, item: &WallpaperItem) {
+    if item.width() > 0 {
+        size_label.set_label(&format!("{} × {}", item.width(), item.height()));
+    } else {
+        size_label.set_label("");
+    }
+}
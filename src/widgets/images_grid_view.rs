@@ -1,19 +1,107 @@
-use gio::glib::{self, object::Cast};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use gio::glib::{self, Propagation, object::Cast};
+use gtk::gdk::{Key, ModifierType};
 use gtk::prelude::*;
-use gtk::{GridView, ListItem, ScrolledWindow, SignalListItemFactory, SingleSelection, StringList};
+use gtk::{
+    CustomFilter, EventControllerKey, PropagationPhase, CustomSorter, FilterChange, FilterListModel, GridView, ListItem, ListView, ScrolledWindow, SignalListItemFactory, SingleSelection, SortListModel, SorterChange
+};
 
+use crate::app_model::app_model;
+use crate::images::filter::{ImageFilter, ImageSort};
+use crate::images::image_info::ImageInfo;
+use crate::images::scan::{is_animated, is_video};
+use crate::images::wallpaper_item::WallpaperItem;
+use crate::favorites::is_favorite;
+use crate::hypr::hyprctl::list_active;
+use crate::hypr::hyprpaper::WallpaperEntry;
+use crate::state::{get_monitors_showing, get_preview, get_wallpapers};
 use crate::widgets::image_card::ImageCard;
+use crate::widgets::image_row::ImageRow;
+
+// Small cards on a wide window fill the row instead of leaving space to the sides.
+const MAX_COLUMNS: u32 = 32;
 
+#[derive(Clone)]
 pub struct ImagesGridView {
     pub widget: ScrolledWindow,
-    pub images_path_list: StringList,
+    grid_view: GridView,
+    list_view: ListView,
+    selection_model: SingleSelection,
+    images: gio::ListStore,
+    filter: CustomFilter,
+    sorter: CustomSorter,
+    image_filter: Rc<RefCell<ImageFilter>>,
+    image_sort: Rc<Cell<ImageSort>>,
+    sort_descending: Rc<Cell<bool>>,
+    active_request: Rc<Cell<u64>>,
 }
 
 impl ImagesGridView {
-    pub fn new(on_image_clicked: fn(path: &str)) -> Self {
+    pub fn new(on_image_clicked: impl Fn(&str) + 'static, on_image_activated: impl Fn(&str) + 'static) -> Self {
+        let on_image_clicked: Rc<dyn Fn(&str)> = Rc::new(on_image_clicked);
         let factory = SignalListItemFactory::new();
-        let images_path_list = StringList::new(&[]);
-        let selection_model = SingleSelection::builder().model(&images_path_list).build();
+        let images = gio::ListStore::new::<WallpaperItem>();
+
+        let image_filter = Rc::new(RefCell::new(ImageFilter::default()));
+        let image_sort = Rc::new(Cell::new(ImageSort::default()));
+        let sort_descending = Rc::new(Cell::new(false));
+
+        let filter = CustomFilter::new(glib::clone!(
+            #[strong]
+            image_filter,
+            move |obj| {
+                obj.downcast_ref::<WallpaperItem>()
+                    .is_some_and(|item| image_filter.borrow().matches(item))
+            }
+        ));
+        let sorter = CustomSorter::new(glib::clone!(
+            #[strong]
+            image_sort,
+            #[strong]
+            sort_descending,
+            move |a, b| {
+                let (Some(a), Some(b)) = (a.downcast_ref::<WallpaperItem>(), b.downcast_ref::<WallpaperItem>()) else {
+                    return gtk::Ordering::Equal;
+                };
+                let ordering = image_sort.get().compare(a, b);
+                if sort_descending.get() {
+                    ordering.reverse().into()
+                } else {
+                    ordering.into()
+                }
+            }
+        ));
+
+        let filter_model = FilterListModel::new(Some(images.clone()), Some(filter.clone()));
+        let sort_model = SortListModel::new(Some(filter_model), Some(sorter.clone()));
+        let selection_model = SingleSelection::builder().model(&sort_model).build();
+
+        let preview_key_controller = |selection_model: &SingleSelection, on_image_clicked: &Rc<dyn Fn(&str)>| {
+            // Space would otherwise toggle the selection, it previews the selected image instead.
+            let key_controller = EventControllerKey::new();
+            key_controller.set_propagation_phase(PropagationPhase::Capture);
+            key_controller.connect_key_pressed(glib::clone!(
+                #[strong]
+                selection_model,
+                #[strong]
+                on_image_clicked,
+                move |_, key, _, modifiers| {
+                    if key != Key::space
+                        || modifiers.intersects(ModifierType::CONTROL_MASK | ModifierType::ALT_MASK | ModifierType::SUPER_MASK)
+                    {
+                        return Propagation::Proceed;
+                    }
+                    if let Some(item) = selection_model.selected_item().and_downcast::<WallpaperItem>() {
+                        on_image_clicked(&item.path());
+                    }
+                    Propagation::Stop
+                }
+            ));
+            key_controller
+        };
+        let on_image_activated: Rc<dyn Fn(&str)> = Rc::new(on_image_activated);
 
         let scrolled_window = ScrolledWindow::builder()
             .vscrollbar_policy(gtk::PolicyType::Automatic)
@@ -24,21 +112,78 @@ impl ImagesGridView {
         let grid_view = GridView::builder()
             .model(&selection_model)
             .factory(&factory)
-            .min_columns(2)
-            .max_columns(6)
-            .single_click_activate(true)
+            .min_columns(1)
+            .max_columns(MAX_COLUMNS)
             .build();
         grid_view.add_css_class("image-browser-grid");
 
-        factory.connect_setup(|_, obj: &glib::Object| {
+        grid_view.connect_activate(glib::clone!(
+            #[strong]
+            on_image_activated,
+            move |grid_view, position| {
+                if let Some(item) = grid_view
+                    .model()
+                    .and_then(|model| model.item(position))
+                    .and_downcast::<WallpaperItem>()
+                {
+                    on_image_activated(&item.path());
+                }
+            }
+        ));
+        grid_view.add_controller(preview_key_controller(&selection_model, &on_image_clicked));
+
+        let row_factory = SignalListItemFactory::new();
+        row_factory.connect_setup(glib::clone!(
+            #[strong]
+            on_image_clicked,
+            move |_, obj: &glib::Object| {
+                let list_item: &ListItem = obj.downcast_ref::<ListItem>().unwrap();
+                let image_row = ImageRow::new(on_image_clicked.clone());
+                list_item.set_child(Some(&image_row.widget));
+                unsafe {
+                    list_item.set_data("image_row", image_row);
+                }
+            }
+        ));
+        row_factory.connect_bind(|_, obj: &glib::Object| {
+            let list_item: &ListItem = obj.downcast_ref::<ListItem>().unwrap();
+            if let Some(wallpaper_item) = list_item.item().and_downcast::<WallpaperItem>() {
+                let image_row: &ImageRow = unsafe { list_item.data("image_row").unwrap().as_ref() };
+                image_row.bind(&wallpaper_item);
+            }
+        });
+        row_factory.connect_unbind(|_, obj: &glib::Object| {
+            let list_item: &ListItem = obj.downcast_ref::<ListItem>().unwrap();
+            let image_row: &ImageRow = unsafe { list_item.data("image_row").unwrap().as_ref() };
+            image_row.unbind();
+        });
+
+        // Shares the selection with the grid, switching views keeps the selected image.
+        let list_view = ListView::builder()
+            .model(&selection_model)
+            .factory(&row_factory)
+            .build();
+        list_view.add_css_class("image-browser-list");
+        list_view.connect_activate(move |list_view, position| {
+            if let Some(item) = list_view
+                .model()
+                .and_then(|model| model.item(position))
+                .and_downcast::<WallpaperItem>()
+            {
+                on_image_activated(&item.path());
+            }
+        });
+        list_view.add_controller(preview_key_controller(&selection_model, &on_image_clicked));
+
+        factory.connect_setup(move |_, obj: &glib::Object| {
             let list_item: &ListItem = obj.downcast_ref::<ListItem>().unwrap();
-            let image_card = ImageCard::new();
+            let image_card = ImageCard::new(on_image_clicked.clone());
             list_item.set_child(Some(&image_card.widget));
             unsafe {
                 list_item.set_data("image_card", image_card);
             }
         });
-        factory.connect_bind(move |_, obj: &glib::Object| {
+        factory.connect_bind(|_, obj: &glib::Object| {
             let list_item: &ListItem = obj.downcast_ref::<ListItem>().unwrap();
             let item_obj = list_item.item();
             if item_obj.is_none() {
@@ -46,22 +191,183 @@ impl ImagesGridView {
             }
             let item = item_obj.unwrap();
 
-            let string_obj = item.downcast_ref::<gtk::StringObject>();
-            if string_obj.is_none() {
+            let wallpaper_item = item.downcast_ref::<WallpaperItem>();
+            if wallpaper_item.is_none() {
                 return;
             }
-            let full_path = string_obj.unwrap().string();
 
             let image_card: &ImageCard = unsafe { list_item.data("image_card").unwrap().as_ref() };
 
-            image_card.set_image(full_path.to_string(), on_image_clicked);
+            image_card.bind(wallpaper_item.unwrap());
+        });
+        factory.connect_unbind(|_, obj: &glib::Object| {
+            let list_item: &ListItem = obj.downcast_ref::<ListItem>().unwrap();
+            let image_card: &ImageCard = unsafe { list_item.data("image_card").unwrap().as_ref() };
+            image_card.unbind();
         });
 
         scrolled_window.set_child(Some(&grid_view));
 
-        Self {
+        let images_grid_view = Self {
             widget: scrolled_window,
-            images_path_list,
+            grid_view,
+            list_view,
+            selection_model,
+            images,
+            filter,
+            sorter,
+            image_filter,
+            image_sort,
+            sort_descending,
+            active_request: Rc::new(Cell::new(0)),
+        };
+
+        app_model().connect_wallpaper_changed(glib::clone!(
+            #[strong]
+            images_grid_view,
+            move |_| images_grid_view.refresh_active_monitors()
+        ));
+        app_model().connect_monitors_changed(glib::clone!(
+            #[strong]
+            images_grid_view,
+            move |_| images_grid_view.refresh_active_monitors()
+        ));
+
+        images_grid_view
+    }
+
+    pub fn set_paths(&self, paths: Vec<String>) {
+        let items: Vec<WallpaperItem> = paths
+            .iter()
+            .map(|path| {
+                let item = WallpaperItem::new(path);
+                item.set_favorite(is_favorite(path));
+                item
+            })
+            .collect();
+        self.images.splice(0, self.images.n_items(), &items);
+        self.refresh_active_monitors();
+
+        // Headers are read off the UI thread, sorting and filtering by them starts once all are in.
+        let filter = self.filter.clone();
+        let sorter = self.sorter.clone();
+        glib::spawn_future_local(async move {
+            let read_infos = move || {
+                paths
+                    .iter()
+                    .map(|path| {
+                        let animated = is_animated(path);
+                        (ImageInfo::read(path), animated, animated && is_video(path))
+                    })
+                    .collect::<Vec<_>>()
+            };
+            let Ok(infos) = gio::spawn_blocking(read_infos).await else {
+                return;
+            };
+            for (item, (info, animated, video)) in items.iter().zip(infos) {
+                item.set_info(info);
+                if animated {
+                    item.set_media(animated, video);
+                }
+            }
+            filter.changed(FilterChange::Different);
+            sorter.changed(SorterChange::Different);
+        });
+    }
+
+    // The images left after filtering, in the order they are shown.
+    pub fn visible_paths(&self) -> Vec<String> {
+        self.selection_model
+            .iter::<glib::Object>()
+            .flatten()
+            .filter_map(|object| object.downcast::<WallpaperItem>().ok())
+            .map(|item| item.path())
+            .collect()
+    }
+
+    pub fn find_item(&self, path: &str) -> Option<WallpaperItem> {
+        self.images
+            .iter::<WallpaperItem>()
+            .flatten()
+            .find(|item| item.path() == path)
+    }
+
+    pub fn remove_item(&self, path: &str) {
+        if let Some(position) = self
+            .images
+            .iter::<WallpaperItem>()
+            .flatten()
+            .position(|item| item.path() == path)
+        {
+            self.images.remove(position as u32);
+        }
+    }
+
+    // Badges follow what hyprpaper shows, so wallpapers other tools set over its IPC show up too.
+    // Without an answer from hyprpaper, or while a preview is up, they follow the config.
+    pub fn refresh_active_monitors(&self) {
+        let request = self.active_request.get() + 1;
+        self.active_request.set(request);
+
+        let images_grid_view = self.clone();
+        glib::spawn_future_local(async move {
+            let active = gio::spawn_blocking(list_active).await.ok().flatten();
+            if images_grid_view.active_request.get() != request {
+                return;
+            }
+            let wallpapers = match active {
+                Some(active) if get_preview().is_none() => active,
+                _ => get_wallpapers(),
+            };
+            images_grid_view.update_active_monitors(&wallpapers);
+        });
+    }
+
+    fn update_active_monitors(&self, wallpapers: &[WallpaperEntry]) {
+        for item in self.images.iter::<WallpaperItem>().flatten() {
+            let monitors = get_monitors_showing(&item.path(), wallpapers);
+            if item.active_monitors() != monitors {
+                item.set_active_monitors(monitors);
+            }
+        }
+    }
+
+    pub fn connect_selection_changed(&self, f: impl Fn(Option<WallpaperItem>) + 'static) {
+        self.selection_model.connect_selected_item_notify(move |selection_model| {
+            f(selection_model.selected_item().and_downcast::<WallpaperItem>());
+        });
+    }
+
+    // Lays the images out in one row that scrolls sideways.
+    pub fn set_single_row(&self) {
+        self.grid_view.set_orientation(gtk::Orientation::Horizontal);
+        self.grid_view.set_min_columns(1);
+        self.grid_view.set_max_columns(1);
+        self.widget.set_policy(gtk::PolicyType::Automatic, gtk::PolicyType::Never);
+    }
+
+    pub fn set_list_mode(&self, list_mode: bool) {
+        if list_mode {
+            self.widget.set_child(Some(&self.list_view));
+        } else {
+            self.widget.set_child(Some(&self.grid_view));
+        }
+    }
+
+    pub fn grab_focus(&self) {
+        if let Some(child) = self.widget.child() {
+            child.grab_focus();
         }
     }
+
+    pub fn update_filter(&self, update: impl FnOnce(&mut ImageFilter)) {
+        update(&mut self.image_filter.borrow_mut());
+        self.filter.changed(FilterChange::Different);
+    }
+
+    pub fn set_sort(&self, sort: ImageSort, descending: bool) {
+        self.image_sort.set(sort);
+        self.sort_descending.set(descending);
+        self.sorter.changed(SorterChange::Different);
+    }
 }
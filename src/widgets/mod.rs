@@ -1,2 +1,8 @@
+pub mod details_panel;
+pub mod diagnostics_banner;
+pub mod grid_controls;
 pub mod image_card;
+pub mod image_row;
 pub mod images_grid_view;
+pub mod monitor_bar;
+pub mod preview_bar;
@@ -0,0 +1,146 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gio::glib::clone;
+use gtk::prelude::*;
+use gtk::{Box, DropTarget, Label, Orientation, ToggleButton};
+
+use crate::app_model::app_model;
+use crate::settings::{get_settings, update_settings};
+use crate::state::{get_monitors, get_selected_monitor, has_empty_monitor_name, has_monitor, has_more_than_one_monitors, set_selected_monitor};
+
+// The "All" toggle comes first, its monitor is the empty string.
+#[derive(Clone)]
+pub struct MonitorBar {
+    pub widget: Box,
+    toggle_buttons: Rc<RefCell<Vec<(String, ToggleButton)>>>,
+    drop_target: Rc<dyn Fn(Option<String>) -> DropTarget>,
+}
+
+impl MonitorBar {
+    pub fn new(drop_target: impl Fn(Option<String>) -> DropTarget + 'static) -> Self {
+        let widget = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(5)
+            .hexpand(true)
+            .build();
+
+        let monitor_bar = Self {
+            widget,
+            toggle_buttons: Rc::new(RefCell::new(Vec::new())),
+            drop_target: Rc::new(drop_target),
+        };
+        monitor_bar.rebuild();
+        monitor_bar.select_configured();
+
+        app_model().connect_monitors_changed(clone!(
+            #[strong]
+            monitor_bar,
+            move |_| {
+                monitor_bar.rebuild();
+                monitor_bar.select(&get_selected_monitor());
+            }
+        ));
+        app_model().connect_selected_monitor_changed(clone!(
+            #[strong]
+            monitor_bar,
+            move |_, monitor| {
+                monitor_bar.select(monitor);
+                update_settings(|settings| settings.selected_monitor = Some(monitor.to_string()));
+            }
+        ));
+
+        monitor_bar
+    }
+
+    fn rebuild(&self) {
+        while let Some(child) = self.widget.first_child() {
+            self.widget.remove(&child);
+        }
+        let mut toggle_buttons = self.toggle_buttons.borrow_mut();
+        toggle_buttons.clear();
+
+        self.widget.set_visible(has_more_than_one_monitors());
+        if !has_more_than_one_monitors() {
+            return;
+        }
+
+        self.widget.append(&Label::builder().label("Monitors: ").build());
+        let all_monitors_toggle_button = ToggleButton::builder().label("All").build();
+        all_monitors_toggle_button.connect_toggled(|tg| {
+            if tg.is_active() {
+                set_selected_monitor("".to_string());
+            }
+        });
+        self.widget.append(&all_monitors_toggle_button);
+        toggle_buttons.push((String::new(), all_monitors_toggle_button.clone()));
+
+        for monitor in get_monitors() {
+            let toggle_button = ToggleButton::builder()
+                .label(&monitor)
+                .group(&all_monitors_toggle_button)
+                .build();
+            toggle_button.connect_toggled(clone!(
+                #[strong]
+                monitor,
+                move |tg| {
+                    if tg.is_active() {
+                        set_selected_monitor(monitor.clone());
+                    }
+                }
+            ));
+            self.widget.append(&toggle_button);
+            toggle_buttons.push((monitor, toggle_button));
+        }
+
+        for (monitor, toggle_button) in toggle_buttons.iter() {
+            toggle_button.add_controller((self.drop_target)((!monitor.is_empty()).then(|| monitor.clone())));
+        }
+    }
+
+    // At startup the last selection comes back. Without one it follows the config: "All" when it
+    // has an entry for all monitors, otherwise the last monitor that has its own wallpaper.
+    fn select_configured(&self) {
+        let toggle_buttons = self.toggle_buttons.borrow();
+        let Some((_, all_monitors_toggle_button)) = toggle_buttons.first() else {
+            return;
+        };
+        if let Some((monitor, toggle_button)) = get_settings()
+            .selected_monitor
+            .and_then(|selected| toggle_buttons.iter().find(|(monitor, _)| *monitor == selected))
+        {
+            toggle_button.set_active(true);
+            set_selected_monitor(monitor.clone());
+            return;
+        }
+        all_monitors_toggle_button.set_active(has_empty_monitor_name());
+        for (monitor, toggle_button) in toggle_buttons.iter().skip(1) {
+            if has_monitor(monitor.clone()) {
+                toggle_button.set_active(true);
+                set_selected_monitor(monitor.clone());
+            }
+        }
+    }
+
+    // A monitor that went away falls back to "All".
+    fn select(&self, monitor: &str) {
+        let toggle_buttons = self.toggle_buttons.borrow();
+        match toggle_buttons.iter().find(|(name, _)| name == monitor) {
+            Some((_, toggle_button)) => toggle_button.set_active(true),
+            None => {
+                if let Some((_, all_monitors_toggle_button)) = toggle_buttons.first() {
+                    all_monitors_toggle_button.set_active(true);
+                }
+            }
+        }
+    }
+
+    // Index 0 is "All", so the number keys line up with the monitors.
+    pub fn activate_nth(&self, index: usize) -> bool {
+        let Some((_, toggle_button)) = self.toggle_buttons.borrow().get(index).cloned() else {
+            return false;
+        };
+        toggle_button.set_active(true);
+        true
+    }
+}
@@ -0,0 +1,107 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use gio::glib::{self, ControlFlow, SourceId, clone};
+use gtk::prelude::*;
+use gtk::{Box, Button, Label, Orientation};
+
+use crate::hypr::hyprctl::{apply_preview, cancel_preview, preview_wallpaper};
+
+const PREVIEW_TIMEOUT_SECONDS: u32 = 15;
+
+#[derive(Clone)]
+pub struct PreviewBar {
+    pub widget: Box,
+    label: Label,
+    remaining: Rc<Cell<u32>>,
+    timer: Rc<RefCell<Option<SourceId>>>,
+}
+
+impl PreviewBar {
+    pub fn new() -> Self {
+        let preview_box = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(5)
+            .visible(false)
+            .build();
+        preview_box.add_css_class("preview-bar");
+
+        let label = Label::builder().halign(gtk::Align::Start).build();
+        preview_box.append(&label);
+
+        let apply_button = Button::builder().label("Apply").build();
+        apply_button.add_css_class("suggested-action");
+        preview_box.append(&apply_button);
+
+        let cancel_button = Button::builder().label("Cancel").build();
+        preview_box.append(&cancel_button);
+
+        let preview_bar = Self {
+            widget: preview_box,
+            label,
+            remaining: Rc::new(Cell::new(0)),
+            timer: Rc::new(RefCell::new(None)),
+        };
+
+        apply_button.connect_clicked(clone!(
+            #[strong]
+            preview_bar,
+            move |_| {
+                preview_bar.stop();
+                apply_preview();
+            }
+        ));
+        cancel_button.connect_clicked(clone!(
+            #[strong]
+            preview_bar,
+            move |_| {
+                preview_bar.stop();
+                cancel_preview();
+            }
+        ));
+
+        preview_bar
+    }
+
+    pub fn start(&self, path: &str) {
+        self.stop();
+        preview_wallpaper(path.to_owned());
+
+        self.remaining.set(PREVIEW_TIMEOUT_SECONDS);
+        self.update_label();
+        self.widget.set_visible(true);
+
+        let timer = glib::timeout_add_seconds_local(
+            1,
+            clone!(
+                #[strong(rename_to = preview_bar)]
+                self,
+                move || {
+                    let remaining = preview_bar.remaining.get().saturating_sub(1);
+                    preview_bar.remaining.set(remaining);
+                    if remaining > 0 {
+                        preview_bar.update_label();
+                        return ControlFlow::Continue;
+                    }
+                    preview_bar.timer.take();
+                    preview_bar.widget.set_visible(false);
+                    cancel_preview();
+                    ControlFlow::Break
+                }
+            ),
+        );
+        self.timer.replace(Some(timer));
+    }
+
+    pub fn stop(&self) {
+        if let Some(timer) = self.timer.take() {
+            timer.remove();
+        }
+        self.widget.set_visible(false);
+    }
+
+    fn update_label(&self) {
+        self.label
+            .set_label(&format!("Previewing, reverting in {}s", self.remaining.get()));
+    }
+}
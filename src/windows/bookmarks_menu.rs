@@ -0,0 +1,53 @@
+use std::path::Path;
+
+use gio::{Menu, MenuItem};
+use gtk::MenuButton;
+use gtk::prelude::*;
+
+use crate::settings::get_settings;
+
+// The menu is rebuilt whenever it opens, bookmarks can change in the Preferences window meanwhile.
+pub struct BookmarksMenu {
+    pub widget: MenuButton,
+}
+
+impl BookmarksMenu {
+    pub fn new() -> Self {
+        let menu = Menu::new();
+        fill_bookmarks_menu(&menu);
+
+        let widget = MenuButton::builder()
+            .icon_name("user-bookmarks-symbolic")
+            .menu_model(&menu)
+            .tooltip_text("Bookmarked Folders")
+            .build();
+        widget.connect_active_notify(move |button| {
+            if button.is_active() {
+                fill_bookmarks_menu(&menu);
+            }
+        });
+
+        Self { widget }
+    }
+}
+
+fn fill_bookmarks_menu(menu: &Menu) {
+    menu.remove_all();
+
+    let bookmarks_section = Menu::new();
+    for bookmark in get_settings().bookmarks {
+        let name = Path::new(&bookmark)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| bookmark.clone());
+        let item = MenuItem::new(Some(&name), None);
+        item.set_action_and_target_value(Some("win.open-folder"), Some(&bookmark.to_variant()));
+        bookmarks_section.append_item(&item);
+    }
+    menu.append_section(None, &bookmarks_section);
+
+    let manage_section = Menu::new();
+    manage_section.append(Some("Bookmark This Folder"), Some("win.bookmark-folder"));
+    manage_section.append(Some("Preferences…"), Some("win.preferences"));
+    menu.append_section(None, &manage_section);
+}
@@ -0,0 +1,187 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use gio::glib::clone;
+use gtk::prelude::*;
+use gtk::{
+    Align, ApplicationWindow, Box, Button, ContentFit, GestureDrag, Label, Orientation, Overlay, Picture, Scale, Window
+};
+
+use crate::images::crop::crop_image;
+use crate::images::image_info::ImageInfo;
+use crate::windows::{close_on_escape, dialog_box};
+
+const MAX_DISPLAY_WIDTH: f64 = 720.0;
+const MAX_DISPLAY_HEIGHT: f64 = 480.0;
+const MIN_ZOOM: f64 = 0.2;
+
+// Everything is tracked in display coordinates and only converted to image pixels when cropping.
+struct CropArea {
+    display_width: f64,
+    display_height: f64,
+    aspect_ratio: f64,
+    x: Cell<f64>,
+    y: Cell<f64>,
+    zoom: Cell<f64>,
+}
+
+impl CropArea {
+    fn size(&self) -> (f64, f64) {
+        let (width, height) = if self.display_width / self.display_height > self.aspect_ratio {
+            (self.display_height * self.aspect_ratio, self.display_height)
+        } else {
+            (self.display_width, self.display_width / self.aspect_ratio)
+        };
+        (width * self.zoom.get(), height * self.zoom.get())
+    }
+
+    fn move_to(&self, x: f64, y: f64) {
+        let (width, height) = self.size();
+        self.x.set(x.clamp(0.0, self.display_width - width));
+        self.y.set(y.clamp(0.0, self.display_height - height));
+    }
+
+    fn update(&self, rect: &Box) {
+        let (width, height) = self.size();
+        rect.set_margin_start(self.x.get().round() as i32);
+        rect.set_margin_top(self.y.get().round() as i32);
+        rect.set_size_request(width.round() as i32, height.round() as i32);
+    }
+}
+
+// Picks the part of the image that covers a monitor of the target size without distortion.
+pub fn show_crop_window(parent: &ApplicationWindow, path: &str, target: (i32, i32), on_cropped: impl Fn(String) + 'static) {
+    let Some(info) = ImageInfo::read(path).filter(|info| info.width > 0 && info.height > 0) else {
+        eprintln!("Could not read the size of {}", path);
+        return;
+    };
+
+    let display_scale = (MAX_DISPLAY_WIDTH / info.width as f64)
+        .min(MAX_DISPLAY_HEIGHT / info.height as f64)
+        .min(1.0);
+    let area = Rc::new(CropArea {
+        display_width: info.width as f64 * display_scale,
+        display_height: info.height as f64 * display_scale,
+        aspect_ratio: target.0 as f64 / target.1 as f64,
+        x: Cell::new(0.0),
+        y: Cell::new(0.0),
+        zoom: Cell::new(1.0),
+    });
+
+    let picture = Picture::builder()
+        .file(&gio::File::for_path(path))
+        .content_fit(ContentFit::Fill)
+        .can_shrink(true)
+        .width_request(area.display_width.round() as i32)
+        .height_request(area.display_height.round() as i32)
+        .build();
+
+    let rect = Box::builder().halign(Align::Start).valign(Align::Start).build();
+    rect.add_css_class("crop-rect");
+
+    let overlay = Overlay::builder()
+        .child(&picture)
+        .halign(Align::Center)
+        .valign(Align::Center)
+        .build();
+    overlay.add_overlay(&rect);
+
+    let (width, height) = area.size();
+    area.move_to((area.display_width - width) / 2.0, (area.display_height - height) / 2.0);
+    area.update(&rect);
+
+    let drag_start = Rc::new(Cell::new((0.0, 0.0)));
+    let drag = GestureDrag::new();
+    drag.connect_drag_begin(clone!(
+        #[strong]
+        area,
+        #[strong]
+        drag_start,
+        move |_, _, _| {
+            drag_start.set((area.x.get(), area.y.get()));
+        }
+    ));
+    drag.connect_drag_update(clone!(
+        #[strong]
+        area,
+        #[weak]
+        rect,
+        move |_, offset_x, offset_y| {
+            let (start_x, start_y) = drag_start.get();
+            area.move_to(start_x + offset_x, start_y + offset_y);
+            area.update(&rect);
+        }
+    ));
+    overlay.add_controller(drag);
+
+    let zoom_scale = Scale::with_range(Orientation::Horizontal, MIN_ZOOM, 1.0, 0.05);
+    zoom_scale.set_value(1.0);
+    zoom_scale.set_hexpand(true);
+    zoom_scale.connect_value_changed(clone!(
+        #[strong]
+        area,
+        #[weak]
+        rect,
+        move |scale| {
+            // Zooming keeps the center of the crop where it is.
+            let (old_width, old_height) = area.size();
+            let (center_x, center_y) = (area.x.get() + old_width / 2.0, area.y.get() + old_height / 2.0);
+            area.zoom.set(scale.value());
+            let (width, height) = area.size();
+            area.move_to(center_x - width / 2.0, center_y - height / 2.0);
+            area.update(&rect);
+        }
+    ));
+
+    let cancel_button = Button::builder().label("Cancel").build();
+    let crop_button = Button::builder().label("Crop and Apply").build();
+    crop_button.add_css_class("suggested-action");
+
+    let controls_box = Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(8)
+        .build();
+    controls_box.append(&Label::new(Some("Zoom")));
+    controls_box.append(&zoom_scale);
+    controls_box.append(&cancel_button);
+    controls_box.append(&crop_button);
+
+    let content = dialog_box(Orientation::Vertical, 12);
+    content.append(&overlay);
+    content.append(&controls_box);
+
+    let window = Window::builder()
+        .title(format!("Crop for {}×{}", target.0, target.1))
+        .transient_for(parent)
+        .modal(true)
+        .resizable(false)
+        .child(&content)
+        .build();
+
+    cancel_button.connect_clicked(clone!(
+        #[weak]
+        window,
+        move |_| window.close()
+    ));
+
+    let path = path.to_owned();
+    crop_button.connect_clicked(clone!(
+        #[weak]
+        window,
+        move |_| {
+            let (width, height) = area.size();
+            let to_image = |value: f64| (value / display_scale).round() as i32;
+            match crop_image(&path, to_image(area.x.get()), to_image(area.y.get()), to_image(width), to_image(height)) {
+                Ok(cropped) => {
+                    window.close();
+                    on_cropped(cropped);
+                }
+                Err(err) => eprintln!("{}", err),
+            }
+        }
+    ));
+
+    close_on_escape(&window);
+
+    window.present();
+}
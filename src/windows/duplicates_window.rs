@@ -0,0 +1,263 @@
+use std::cell::RefCell;
+use std::path::Path;
+use std::rc::Rc;
+
+use gio::{Cancellable, File};
+use gio::glib::{self, clone};
+use gtk::prelude::*;
+use gtk::{
+    Align, ApplicationWindow, Box, Button, CheckButton, ContentFit, Label, Orientation, Picture, ScrolledWindow, SpinButton, Window
+};
+
+use crate::images::duplicates::{DEFAULT_THRESHOLD, Duplicate, find_duplicates};
+use crate::images::scan::read_image_entries_recursive;
+use crate::widgets::images_grid_view::ImagesGridView;
+use crate::windows::{close_on_escape, confirm, dialog_box, refuse_trashing_shown};
+
+pub fn show_duplicates_window(parent: &ApplicationWindow, dir: &str, images_grid_view: &ImagesGridView) {
+    let threshold_spin_button = SpinButton::with_range(0.0, 16.0, 1.0);
+    threshold_spin_button.set_value(DEFAULT_THRESHOLD as f64);
+    threshold_spin_button.set_tooltip_text(Some("Differing hash bits still counted as a duplicate"));
+
+    let scan_button = Button::builder().label("Scan").build();
+    let select_button = Button::builder()
+        .label("Select All but the Best")
+        .tooltip_text("Select every copy but the highest resolution one of each group")
+        .sensitive(false)
+        .build();
+    let trash_button = Button::builder()
+        .label("Move Selected to Trash")
+        .sensitive(false)
+        .build();
+    trash_button.add_css_class("destructive-action");
+
+    let status_label = Label::builder().halign(Align::Start).hexpand(true).build();
+
+    let controls_box = Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(8)
+        .build();
+    controls_box.append(&status_label);
+    controls_box.append(&Label::new(Some("Threshold")));
+    controls_box.append(&threshold_spin_button);
+    controls_box.append(&scan_button);
+    controls_box.append(&select_button);
+    controls_box.append(&trash_button);
+
+    let groups_box = Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(16)
+        .build();
+    let scrolled_window = ScrolledWindow::builder()
+        .child(&groups_box)
+        .vexpand(true)
+        .min_content_height(400)
+        .min_content_width(720)
+        .build();
+
+    let content = dialog_box(Orientation::Vertical, 12);
+    content.append(&controls_box);
+    content.append(&scrolled_window);
+
+    let window = Window::builder()
+        .title(format!("Duplicates in {}", dir))
+        .transient_for(parent)
+        .modal(true)
+        .default_width(820)
+        .default_height(560)
+        .child(&content)
+        .build();
+
+    // Every shown copy with the check button that selects it for the trash.
+    let trash_checks: Rc<RefCell<Vec<TrashCheck>>> = Rc::new(RefCell::new(Vec::new()));
+
+    let dir = dir.to_owned();
+    let scan = clone!(
+        #[strong]
+        trash_checks,
+        #[weak]
+        threshold_spin_button,
+        #[weak]
+        scan_button,
+        #[weak]
+        trash_button,
+        #[weak]
+        select_button,
+        #[weak]
+        status_label,
+        #[weak]
+        groups_box,
+        move || {
+            while let Some(child) = groups_box.first_child() {
+                groups_box.remove(&child);
+            }
+            trash_checks.borrow_mut().clear();
+            status_label.set_label("Scanning…");
+            scan_button.set_sensitive(false);
+            select_button.set_sensitive(false);
+            trash_button.set_sensitive(false);
+
+            let dir = dir.clone();
+            let threshold = threshold_spin_button.value_as_int() as u32;
+            glib::spawn_future_local(clone!(
+                #[strong]
+                trash_checks,
+                async move {
+                    let groups = gio::spawn_blocking(move || find_duplicates(&read_image_entries_recursive(&dir), threshold))
+                        .await
+                        .unwrap_or_default();
+
+                    status_label.set_label(&match groups.len() {
+                        0 => "No duplicates found".to_string(),
+                        1 => "1 group of duplicates".to_string(),
+                        count => format!("{} groups of duplicates", count),
+                    });
+                    for group in &groups {
+                        groups_box.append(&group_row(group, &mut trash_checks.borrow_mut()));
+                    }
+                    // Nothing is selected for the trash until the user picks it.
+                    for trash_check in trash_checks.borrow().iter() {
+                        trash_check.check_button.connect_toggled(clone!(
+                            #[weak(rename_to = trash_checks)]
+                            trash_checks,
+                            #[weak]
+                            trash_button,
+                            move |_| {
+                                trash_button.set_sensitive(trash_checks.borrow().iter().any(|check| check.check_button.is_active()));
+                            }
+                        ));
+                    }
+                    scan_button.set_sensitive(true);
+                    select_button.set_sensitive(!groups.is_empty());
+                }
+            ));
+        }
+    );
+
+    scan_button.connect_clicked(clone!(
+        #[strong]
+        scan,
+        move |_| scan()
+    ));
+
+    select_button.connect_clicked(clone!(
+        #[strong]
+        trash_checks,
+        move |_| {
+            for trash_check in trash_checks.borrow().iter() {
+                trash_check.check_button.set_active(!trash_check.is_best);
+            }
+        }
+    ));
+
+    trash_button.connect_clicked(clone!(
+        #[strong]
+        trash_checks,
+        #[strong]
+        images_grid_view,
+        #[strong]
+        scan,
+        #[weak]
+        window,
+        move |_| {
+            let selected: Vec<String> = trash_checks
+                .borrow()
+                .iter()
+                .filter(|trash_check| trash_check.check_button.is_active())
+                .map(|trash_check| trash_check.path.clone())
+                .collect();
+            if selected.is_empty() || refuse_trashing_shown(&window, &selected) {
+                return;
+            }
+
+            let message = match selected.len() {
+                1 => "Move 1 image to the trash?".to_string(),
+                count => format!("Move {} images to the trash?", count),
+            };
+            confirm(&window, &message, &selected.join("\n"), "Move to Trash", clone!(
+                #[strong]
+                images_grid_view,
+                #[strong]
+                scan,
+                move || {
+                    for path in selected {
+                        match File::for_path(&path).trash(None::<&Cancellable>) {
+                            Ok(()) => images_grid_view.remove_item(&path),
+                            Err(err) => eprintln!("Could not move {} to the trash: {}", path, err),
+                        }
+                    }
+                    scan();
+                }
+            ));
+        }
+    ));
+
+    close_on_escape(&window);
+
+    window.present();
+    scan();
+}
+
+struct TrashCheck {
+    path: String,
+    check_button: CheckButton,
+    is_best: bool,
+}
+
+// The first copy is the largest and most worth keeping, none is selected for the trash up front.
+fn group_row(group: &[Duplicate], trash_checks: &mut Vec<TrashCheck>) -> Box {
+    let row = Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(12)
+        .build();
+
+    for (index, duplicate) in group.iter().enumerate() {
+        let card = Box::builder()
+            .orientation(Orientation::Vertical)
+            .spacing(4)
+            .build();
+
+        let picture = Picture::builder()
+            .file(&File::for_path(&duplicate.path))
+            .content_fit(ContentFit::Contain)
+            .width_request(160)
+            .height_request(100)
+            .tooltip_text(&duplicate.path)
+            .build();
+        picture.add_css_class("image-thumb");
+        card.append(&picture);
+
+        let name = Path::new(&duplicate.path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let details = Label::builder()
+            .label(format!(
+                "{}\n{} × {} · {}",
+                name,
+                duplicate.width,
+                duplicate.height,
+                glib::format_size(duplicate.size)
+            ))
+            .ellipsize(gtk::pango::EllipsizeMode::Middle)
+            .max_width_chars(24)
+            .build();
+        details.add_css_class("dim-label");
+        card.append(&details);
+
+        let trash_check = CheckButton::builder()
+            .label("Trash")
+            .halign(Align::Center)
+            .build();
+        card.append(&trash_check);
+        trash_checks.push(TrashCheck {
+            path: duplicate.path.clone(),
+            check_button: trash_check,
+            is_best: index == 0,
+        });
+
+        row.append(&card);
+    }
+
+    row
+}
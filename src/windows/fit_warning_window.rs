@@ -0,0 +1,154 @@
+use gio::glib::clone;
+use gtk::prelude::*;
+use gtk::{Align, ApplicationWindow, Box, Button, Label, Orientation, Window};
+
+use crate::hypr::hyprctl::{Monitor, set_wallpaper_with_mode};
+use crate::hypr::hyprpaper::FitMode;
+use crate::images::fit::Fit;
+use crate::images::image_info::ImageInfo;
+use crate::settings::get_settings;
+use crate::state::get_monitor_details;
+use crate::widgets::preview_bar::PreviewBar;
+use crate::windows::crop_window::show_crop_window;
+use crate::windows::{close_on_escape, dialog_box};
+
+// Applies right away unless the image would be upscaled or heavily cropped on one of the target monitors.
+pub fn apply_wallpaper(parent: &ApplicationWindow, preview_bar: &PreviewBar, monitor: String, path: String) {
+    let problems = find_problems(&monitor, &path);
+    if problems.is_empty() {
+        apply(preview_bar, monitor, path, get_settings().fit_mode);
+        return;
+    }
+
+    let content = dialog_box(Orientation::Vertical, 8);
+
+    let title = Label::builder()
+        .label("This image does not fit well")
+        .halign(Align::Start)
+        .build();
+    title.add_css_class("heading");
+    content.append(&title);
+
+    for (target, fit) in &problems {
+        let label = Label::builder()
+            .label(format!("{} is {} — {}", target.name, target.describe_size(), fit.describe()))
+            .halign(Align::Start)
+            .xalign(0.0)
+            .wrap(true)
+            .build();
+        label.add_css_class("warning");
+        content.append(&label);
+    }
+
+    let cancel_button = Button::builder().label("Cancel").build();
+    let crop_button = Button::builder().label("Crop…").build();
+    let contain_button = Button::builder().label("Apply with Contain").build();
+    let apply_button = Button::builder().label("Apply Anyway").build();
+    apply_button.add_css_class("suggested-action");
+
+    let buttons_box = Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(8)
+        .halign(Align::End)
+        .margin_top(8)
+        .build();
+    buttons_box.append(&cancel_button);
+    buttons_box.append(&crop_button);
+    buttons_box.append(&contain_button);
+    buttons_box.append(&apply_button);
+    content.append(&buttons_box);
+
+    let window = Window::builder()
+        .title("Apply Wallpaper")
+        .transient_for(parent)
+        .modal(true)
+        .resizable(false)
+        .default_width(420)
+        .child(&content)
+        .build();
+
+    cancel_button.connect_clicked(clone!(
+        #[weak]
+        window,
+        move |_| window.close()
+    ));
+
+    // Crop for the largest affected monitor, smaller ones with the same shape get a clean downscale.
+    let target = problems
+        .iter()
+        .map(|(target, _)| (target.width, target.height))
+        .max_by_key(|(width, height)| width * height)
+        .unwrap();
+    crop_button.connect_clicked(clone!(
+        #[weak]
+        window,
+        #[weak]
+        parent,
+        #[strong]
+        preview_bar,
+        #[strong]
+        monitor,
+        #[strong]
+        path,
+        move |_| {
+            window.close();
+            show_crop_window(&parent, &path, target, clone!(
+                #[strong]
+                preview_bar,
+                #[strong]
+                monitor,
+                move |cropped| apply(&preview_bar, monitor.clone(), cropped, FitMode::Cover)
+            ));
+        }
+    ));
+
+    contain_button.connect_clicked(clone!(
+        #[weak]
+        window,
+        #[strong]
+        preview_bar,
+        #[strong]
+        monitor,
+        #[strong]
+        path,
+        move |_| {
+            window.close();
+            apply(&preview_bar, monitor.clone(), path.clone(), FitMode::Contain);
+        }
+    ));
+
+    apply_button.connect_clicked(clone!(
+        #[weak]
+        window,
+        #[strong]
+        preview_bar,
+        move |_| {
+            window.close();
+            apply(&preview_bar, monitor.clone(), path.clone(), get_settings().fit_mode);
+        }
+    ));
+
+    close_on_escape(&window);
+
+    window.present();
+}
+
+fn apply(preview_bar: &PreviewBar, monitor: String, path: String, mode: FitMode) {
+    preview_bar.stop();
+    set_wallpaper_with_mode(monitor, path, mode);
+}
+
+fn find_problems(monitor: &str, path: &str) -> Vec<(Monitor, Fit)> {
+    let Some(info) = ImageInfo::read(path).filter(|info| info.width > 0 && info.height > 0) else {
+        return Vec::new();
+    };
+
+    get_monitor_details()
+        .into_iter()
+        .filter(|target| monitor.is_empty() || target.name == monitor)
+        .filter_map(|target| {
+            let fit = Fit::new(info.width, info.height, target.width, target.height)?;
+            fit.needs_warning().then_some((target, fit))
+        })
+        .collect()
+}
@@ -0,0 +1,151 @@
+use gio::glib::{self, clone};
+use gtk::gdk::RGBA;
+use gtk::prelude::*;
+use gtk::{
+    ApplicationWindow, Button, ColorDialog, ColorDialogButton, DropDown, Label, SpinButton, Window
+};
+
+use crate::hypr::hyprctl::assign_wallpapers;
+use crate::images::generate::{Fill, Generator};
+use crate::palette::Color;
+use crate::state::{get_monitor_details, get_selected_monitor};
+use crate::widgets::preview_bar::PreviewBar;
+use crate::windows::{close_on_escape, dialog_grid};
+
+// Used when hyprctl didn't report a mode for the monitor.
+const FALLBACK_SIZE: (i32, i32) = (1920, 1080);
+
+pub fn show_generator_window(parent: &ApplicationWindow, preview_bar: &PreviewBar) {
+    let fill_drop_down = DropDown::from_strings(&Fill::LABELS);
+    let from_button = ColorDialogButton::builder()
+        .dialog(&ColorDialog::builder().with_alpha(false).build())
+        .rgba(&RGBA::new(0.114, 0.125, 0.129, 1.0))
+        .build();
+    let to_button = ColorDialogButton::builder()
+        .dialog(&ColorDialog::builder().with_alpha(false).build())
+        .rgba(&RGBA::new(0.271, 0.522, 0.533, 1.0))
+        .build();
+    let angle_spin_button = SpinButton::with_range(0.0, 345.0, 15.0);
+    angle_spin_button.set_value(90.0);
+
+    let grid = dialog_grid();
+
+    let rows: [(&str, gtk::Widget); 4] = [
+        ("Fill", fill_drop_down.clone().upcast()),
+        ("Color", from_button.clone().upcast()),
+        ("End color", to_button.clone().upcast()),
+        ("Angle", angle_spin_button.clone().upcast()),
+    ];
+    let mut labels = Vec::new();
+    for (row, (name, widget)) in rows.iter().enumerate() {
+        let name_label = Label::builder().label(*name).halign(gtk::Align::End).build();
+        name_label.add_css_class("dim-label");
+        grid.attach(&name_label, 0, row as i32, 1, 1);
+        grid.attach(widget, 1, row as i32, 1, 1);
+        labels.push(name_label);
+    }
+
+    let update_visibility = clone!(
+        #[weak]
+        fill_drop_down,
+        #[weak]
+        to_button,
+        #[weak]
+        angle_spin_button,
+        #[strong]
+        labels,
+        move || {
+            let fill = Fill::from_index(fill_drop_down.selected());
+            to_button.set_visible(fill != Fill::Solid);
+            labels[2].set_visible(fill != Fill::Solid);
+            angle_spin_button.set_visible(fill == Fill::Linear);
+            labels[3].set_visible(fill == Fill::Linear);
+        }
+    );
+    update_visibility();
+    fill_drop_down.connect_selected_notify(move |_| update_visibility());
+
+    let apply_button = Button::builder().label("Apply").halign(gtk::Align::End).build();
+    apply_button.add_css_class("suggested-action");
+    grid.attach(&apply_button, 0, rows.len() as i32, 2, 1);
+
+    let window = Window::builder()
+        .title("Generate Wallpaper")
+        .transient_for(parent)
+        .modal(true)
+        .resizable(false)
+        .child(&grid)
+        .build();
+
+    apply_button.connect_clicked(clone!(
+        #[weak]
+        window,
+        #[strong]
+        preview_bar,
+        move |button| {
+            let generator = Generator {
+                fill: Fill::from_index(fill_drop_down.selected()),
+                from: to_color(&from_button.rgba()),
+                to: to_color(&to_button.rgba()),
+                angle: angle_spin_button.value(),
+            };
+
+            // Every monitor gets a file at its own resolution, "All" included.
+            let selected_monitor = get_selected_monitor();
+            let targets: Vec<(String, i32, i32)> = get_monitor_details()
+                .into_iter()
+                .filter(|monitor| selected_monitor.is_empty() || monitor.name == selected_monitor)
+                .map(|monitor| {
+                    if monitor.width > 0 && monitor.height > 0 {
+                        (monitor.name, monitor.width, monitor.height)
+                    } else {
+                        (monitor.name, FALLBACK_SIZE.0, FALLBACK_SIZE.1)
+                    }
+                })
+                .collect();
+
+            button.set_sensitive(false);
+            glib::spawn_future_local(clone!(
+                #[strong]
+                preview_bar,
+                async move {
+                    let rendered = gio::spawn_blocking(move || {
+                        targets
+                            .into_iter()
+                            .map(|(monitor, width, height)| (monitor, generator.render(width, height)))
+                            .collect::<Vec<_>>()
+                    })
+                    .await
+                    .unwrap_or_default();
+
+                    let mut assignments = Vec::new();
+                    for (monitor, result) in rendered {
+                        match result {
+                            Ok(path) => assignments.push((monitor, path)),
+                            Err(err) => eprintln!("{}", err),
+                        }
+                    }
+                    // All monitors are written and reloaded at once, not one after another.
+                    if !assignments.is_empty() {
+                        preview_bar.stop();
+                        assign_wallpapers(assignments);
+                    }
+                    window.close();
+                }
+            ));
+        }
+    ));
+
+    close_on_escape(&window);
+
+    window.present();
+}
+
+fn to_color(rgba: &RGBA) -> Color {
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    Color {
+        red: channel(rgba.red()),
+        green: channel(rgba.green()),
+        blue: channel(rgba.blue()),
+    }
+}
@@ -0,0 +1,154 @@
+use gio::{Cancellable, File, Menu, MenuItem, SimpleAction};
+use gio::glib::{self, VariantTy, clone};
+use gtk::ApplicationWindow;
+use gtk::prelude::*;
+
+use crate::desktop::{open_in_image_viewer, show_in_file_manager};
+use crate::favorites::set_favorite;
+use crate::images::wallpaper_item::WallpaperItem;
+use crate::state::{get_monitors, has_more_than_one_monitors};
+use crate::widgets::images_grid_view::ImagesGridView;
+use crate::widgets::preview_bar::PreviewBar;
+use crate::windows::fit_warning_window::apply_wallpaper;
+use crate::windows::{confirm, refuse_trashing_shown};
+use crate::windows::properties_window::show_properties_window;
+
+pub fn add_image_actions(window: &ApplicationWindow, images_grid_view: &ImagesGridView, preview_bar: &PreviewBar) {
+    let set_wallpaper_action = SimpleAction::new("set-wallpaper", Some(VariantTy::new("(ss)").unwrap()));
+    set_wallpaper_action.connect_activate(clone!(
+        #[weak]
+        window,
+        #[strong]
+        preview_bar,
+        move |_, parameter| {
+            if let Some((monitor, path)) = parameter.and_then(|parameter| parameter.get::<(String, String)>()) {
+                apply_wallpaper(&window, &preview_bar, monitor, path);
+            }
+        }
+    ));
+    window.add_action(&set_wallpaper_action);
+
+    add_path_action(window, "open-image", clone!(
+        #[weak]
+        window,
+        move |path| {
+            open_in_image_viewer(path, &WidgetExt::display(&window).app_launch_context());
+        }
+    ));
+
+    add_path_action(window, "show-in-file-manager", show_in_file_manager);
+
+    add_path_action(window, "copy-path", clone!(
+        #[weak]
+        window,
+        move |path| {
+            window.clipboard().set_text(path);
+        }
+    ));
+
+    add_path_action(window, "toggle-favorite", clone!(
+        #[strong]
+        images_grid_view,
+        move |path| {
+            if let Some(item) = images_grid_view.find_item(path) {
+                let favorite = !item.favorite();
+                set_favorite(path, favorite);
+                item.set_favorite(favorite);
+            }
+        }
+    ));
+
+    add_path_action(window, "trash-image", clone!(
+        #[weak]
+        window,
+        #[strong]
+        images_grid_view,
+        move |path| {
+            if refuse_trashing_shown(&window, &[path.to_owned()]) {
+                return;
+            }
+
+            let name = images_grid_view.find_item(path).map(|item| item.name()).unwrap_or_else(|| path.to_owned());
+            let path = path.to_owned();
+            confirm(&window, &format!("Move {} to the trash?", name), &path, "Move to Trash", clone!(
+                #[strong]
+                images_grid_view,
+                #[strong]
+                path,
+                move || match File::for_path(&path).trash(None::<&Cancellable>) {
+                    Ok(()) => images_grid_view.remove_item(&path),
+                    Err(err) => eprintln!("Could not move {} to the trash: {}", path, err),
+                }
+            ));
+        }
+    ));
+
+    add_path_action(window, "image-properties", clone!(
+        #[weak]
+        window,
+        #[strong]
+        images_grid_view,
+        move |path| {
+            if let Some(item) = images_grid_view.find_item(path) {
+                show_properties_window(&window, &item);
+            }
+        }
+    ));
+}
+
+fn add_path_action(window: &ApplicationWindow, name: &str, activate: impl Fn(&str) + 'static) {
+    let action = SimpleAction::new(name, Some(VariantTy::STRING));
+    action.connect_activate(move |_, parameter| {
+        if let Some(path) = parameter.and_then(|parameter| parameter.str()) {
+            activate(path);
+        }
+    });
+    window.add_action(&action);
+}
+
+pub fn image_menu(item: &WallpaperItem) -> Menu {
+    let path = item.path();
+    let menu = Menu::new();
+
+    let set_section = Menu::new();
+    set_section.append_item(&menu_item("Set on all monitors", "win.set-wallpaper", &("", path.as_str()).to_variant()));
+    if has_more_than_one_monitors() {
+        for monitor in get_monitors() {
+            set_section.append_item(&menu_item(
+                &format!("Set on {}", monitor),
+                "win.set-wallpaper",
+                &(monitor.as_str(), path.as_str()).to_variant(),
+            ));
+        }
+    }
+    menu.append_section(None, &set_section);
+
+    let path_variant = path.to_variant();
+    let file_section = Menu::new();
+    file_section.append_item(&menu_item("Open in Image Viewer", "win.open-image", &path_variant));
+    file_section.append_item(&menu_item("Show in File Manager", "win.show-in-file-manager", &path_variant));
+    file_section.append_item(&menu_item("Copy Path", "win.copy-path", &path_variant));
+    menu.append_section(None, &file_section);
+
+    let favorite_label = if item.favorite() {
+        "Remove from Favorites"
+    } else {
+        "Add to Favorites"
+    };
+    let manage_section = Menu::new();
+    manage_section.append_item(&menu_item(favorite_label, "win.toggle-favorite", &path_variant));
+    manage_section.append_item(&menu_item("Move to Trash", "win.trash-image", &path_variant));
+    menu.append_section(None, &manage_section);
+
+    let properties_section = Menu::new();
+    properties_section.append_item(&menu_item("Properties", "win.image-properties", &path_variant));
+    menu.append_section(None, &properties_section);
+
+    menu
+}
+
+fn menu_item(label: &str, action: &str, target: &glib::Variant) -> MenuItem {
+    let item = MenuItem::new(Some(label), None);
+    item.set_action_and_target_value(Some(action), Some(target));
+    item
+}
@@ -1,15 +1,44 @@
-use std::fs;
+use std::cell::{Cell, RefCell};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
-use gio::Cancellable;
-use gio::glib::clone;
-use gtk::{ApplicationWindow, Box, Button, FileDialog, Label, Orientation, StringList};
+use gio::{Cancellable, FileMonitor, SimpleAction};
+use gio::glib::{self, ControlFlow, Propagation, VariantTy, clone};
+use gtk::gdk::{DragAction, FileList, Key, ModifierType};
+use gtk::{ApplicationWindow, Box, Button, DropTarget, EventControllerKey, FileDialog, Label, Orientation, Paned};
 use gtk::{ToggleButton, prelude::*};
 
-use crate::hypr::hyprctl::set_wallpaper;
+use crate::app_model::app_model;
+use crate::hypr::events;
+use crate::hypr::hyprctl::{assign_wallpapers, try_get_monitors};
+use crate::hypr::hyprpaper::HyprpaperConfig;
+use crate::hypr::mpvpaper;
+use crate::images::assign::auto_assign;
+use crate::images::import::import_first_image;
+use crate::images::scan::read_image_entries;
+use crate::power::is_on_battery;
+use crate::settings::{get_settings, update_settings};
 use crate::state::{
-    get_first_wallpaper_path, get_monitors, has_empty_monitor_name, has_monitor, has_more_than_one_monitors, has_wallpapers, set_selected_monitor
+    get_first_wallpaper_path, get_monitor_details, get_selected_monitor, has_wallpapers, set_config, set_monitors
 };
+use crate::widgets::details_panel::DetailsPanel;
+use crate::widgets::diagnostics_banner::DiagnosticsBanner;
+use crate::widgets::grid_controls::GridControls;
 use crate::widgets::images_grid_view::ImagesGridView;
+use crate::widgets::monitor_bar::MonitorBar;
+use crate::widgets::preview_bar::PreviewBar;
+use crate::windows::bookmarks_menu::BookmarksMenu;
+use crate::windows::duplicates_window::show_duplicates_window;
+use crate::windows::fit_warning_window::apply_wallpaper;
+use crate::windows::generator_window::show_generator_window;
+use crate::windows::image_actions::add_image_actions;
+use crate::windows::preferences_window::{add_bookmark, show_preferences_window};
+use crate::windows::profiles_menu::ProfilesMenu;
+use crate::windows::shortcuts_window::show_shortcuts_window;
+
+thread_local! {
+    static DIR_REQUEST: Cell<u64> = const { Cell::new(0) };
+}
 
 pub struct MainWindow {
     pub widget: Box,
@@ -17,9 +46,39 @@ pub struct MainWindow {
 
 impl MainWindow {
     pub fn new(window: &ApplicationWindow) -> Self {
-        let images_grid_view = ImagesGridView::new(|path: &str| {
-            set_wallpaper(path.to_owned());
-        });
+        let preview_bar = PreviewBar::new();
+        let images_grid_view = ImagesGridView::new(
+            clone!(
+                #[strong]
+                preview_bar,
+                move |path: &str| {
+                    preview_bar.start(path);
+                }
+            ),
+            clone!(
+                #[weak]
+                window,
+                #[strong]
+                preview_bar,
+                move |path: &str| {
+                    apply_wallpaper(&window, &preview_bar, get_selected_monitor(), path.to_owned());
+                }
+            ),
+        );
+        // Another tool may have changed a wallpaper over hyprpaper's IPC while the window was in the background.
+        window.connect_is_active_notify(clone!(
+            #[strong]
+            images_grid_view,
+            move |window| {
+                if window.is_active() {
+                    images_grid_view.refresh_active_monitors();
+                }
+            }
+        ));
+        watch_config_files(window);
+        watch_monitors();
+        watch_power_supply();
+        add_image_actions(window, &images_grid_view, &preview_bar);
 
         let main_box = Box::builder()
             .margin_top(12)
@@ -36,71 +95,36 @@ impl MainWindow {
             .build();
         header_box.add_css_class("image-browser-header");
 
-        if has_more_than_one_monitors() {
-            let monitors_box = Box::builder()
-                .orientation(Orientation::Horizontal)
-                .spacing(5)
-                .build();
-            monitors_box.append(&Label::builder().label("Monitors: ").build());
-            let all_monitors_toggle_button = ToggleButton::builder()
-                .label("All")
-                .active(has_empty_monitor_name())
-                .build();
-            all_monitors_toggle_button.connect_toggled(|tg| {
-                if tg.is_active() {
-                    set_selected_monitor("".to_string());
-                }
-            });
-            monitors_box.append(&all_monitors_toggle_button);
-
-            let mut had_selected_a_monitor = false;
-            let monitors_clone = get_monitors();
-            for monitor in monitors_clone {
-                let toggle_button = ToggleButton::builder()
-                    .label(&monitor)
-                    .group(&all_monitors_toggle_button)
-                    .build();
-                if !had_selected_a_monitor && has_monitor(monitor.clone()) {
-                    toggle_button.set_active(true);
-                    had_selected_a_monitor = false;
-                    set_selected_monitor(monitor.clone());
-                }
-                toggle_button.connect_toggled(move |tg| {
-                    if tg.is_active() {
-                        set_selected_monitor(monitor.clone());
-                    }
-                });
-                monitors_box.append(&toggle_button);
-            }
-            header_box.append(&monitors_box);
-            header_box.append(
-                &Box::builder()
-                    .hexpand(true)
-                    .orientation(Orientation::Horizontal)
-                    .build(),
-            );
-        }
-
         let dir_label = Label::builder()
             .halign(gtk::Align::Start)
             .label("Select a directory")
             .build();
         dir_label.add_css_class("image-browser-dir-label");
+
+        let monitor_bar = MonitorBar::new(clone!(
+            #[weak]
+            window,
+            #[strong]
+            dir_label,
+            #[strong]
+            preview_bar,
+            #[upgrade_or_panic]
+            move |monitor| wallpaper_drop_target(&window, monitor, &dir_label, &preview_bar)
+        ));
+        header_box.append(&monitor_bar.widget);
         header_box.append(&dir_label);
 
-        let browse_button = Button::builder()
-            .label("Browse")
-            .halign(gtk::Align::End)
-            .build();
-        browse_button.add_css_class("image_browser-browse");
-        browse_button.connect_clicked(clone!(
+        window.add_controller(wallpaper_drop_target(window, None, &dir_label, &preview_bar));
+
+        let browse_action = SimpleAction::new("browse", None);
+        browse_action.connect_activate(clone!(
             #[weak]
             window,
             #[strong]
             dir_label,
-            #[strong(rename_to = images_path_list)]
-            images_grid_view.images_path_list,
-            move |_| {
+            #[strong]
+            images_grid_view,
+            move |_, _| {
                 let dialog = FileDialog::builder().title("Select Directory").build();
                 dialog.select_folder(
                     Some(&window),
@@ -109,13 +133,13 @@ impl MainWindow {
                         #[strong]
                         dir_label,
                         #[strong]
-                        images_path_list,
+                        images_grid_view,
                         move |res| {
                             match res {
                                 Ok(file) => {
                                     if let Some(path) = file.path() {
                                         if let Some(path_str) = path.to_str() {
-                                            on_dir_selected(path_str, dir_label, images_path_list);
+                                            on_dir_selected(path_str, dir_label, images_grid_view);
                                         } else {
                                             eprintln!("Failed to convert path to string");
                                         }
@@ -130,77 +154,390 @@ impl MainWindow {
                 );
             },
         ));
+        window.add_action(&browse_action);
+
+        let browse_button = Button::builder()
+            .label("Browse")
+            .halign(gtk::Align::End)
+            .action_name("win.browse")
+            .tooltip_text("Browse (Ctrl+O)")
+            .build();
+        browse_button.add_css_class("image_browser-browse");
         header_box.append(&browse_button);
 
+        let open_folder_action = SimpleAction::new("open-folder", Some(VariantTy::STRING));
+        open_folder_action.connect_activate(clone!(
+            #[strong]
+            dir_label,
+            #[strong]
+            images_grid_view,
+            move |_, parameter| {
+                if let Some(path) = parameter.and_then(|parameter| parameter.get::<String>()) {
+                    on_dir_selected(&path, dir_label.clone(), images_grid_view.clone());
+                }
+            }
+        ));
+        window.add_action(&open_folder_action);
+
+        let bookmark_folder_action = SimpleAction::new("bookmark-folder", None);
+        bookmark_folder_action.connect_activate(clone!(
+            #[strong]
+            dir_label,
+            move |_, _| {
+                if Path::new(&dir_label.label()).is_dir() {
+                    add_bookmark(&dir_label.label());
+                }
+            }
+        ));
+        window.add_action(&bookmark_folder_action);
+
+        let bookmarks_menu = BookmarksMenu::new();
+        header_box.append(&bookmarks_menu.widget);
+
+        let preferences_action = SimpleAction::new("preferences", None);
+        preferences_action.connect_activate(clone!(
+            #[weak]
+            window,
+            move |_, _| {
+                show_preferences_window(&window);
+            }
+        ));
+        window.add_action(&preferences_action);
+
+        let generate_action = SimpleAction::new("generate-wallpaper", None);
+        generate_action.connect_activate(clone!(
+            #[weak]
+            window,
+            #[strong]
+            preview_bar,
+            move |_, _| {
+                show_generator_window(&window, &preview_bar);
+            }
+        ));
+        window.add_action(&generate_action);
+
+        let generate_button = Button::builder()
+            .icon_name("color-select-symbolic")
+            .action_name("win.generate-wallpaper")
+            .tooltip_text("Solid Color or Gradient")
+            .build();
+        header_box.append(&generate_button);
+
+        let auto_assign_action = SimpleAction::new("auto-assign", None);
+        auto_assign_action.connect_activate(clone!(
+            #[strong]
+            images_grid_view,
+            #[strong]
+            preview_bar,
+            move |_, _| {
+                let paths = images_grid_view.visible_paths();
+                let monitors = get_monitor_details();
+                glib::spawn_future_local(clone!(
+                    #[strong]
+                    preview_bar,
+                    async move {
+                        let Ok(assignments) = gio::spawn_blocking(move || auto_assign(&paths, &monitors)).await else {
+                            return;
+                        };
+                        if assignments.is_empty() {
+                            eprintln!("No images to assign");
+                            return;
+                        }
+                        preview_bar.stop();
+                        assign_wallpapers(assignments);
+                    }
+                ));
+            }
+        ));
+        window.add_action(&auto_assign_action);
+
+        let auto_assign_button = Button::builder()
+            .icon_name("view-grid-symbolic")
+            .action_name("win.auto-assign")
+            .tooltip_text("Auto-assign to All Monitors")
+            .build();
+        header_box.append(&auto_assign_button);
+
+        let duplicates_action = SimpleAction::new("find-duplicates", None);
+        duplicates_action.connect_activate(clone!(
+            #[weak]
+            window,
+            #[strong]
+            dir_label,
+            #[strong]
+            images_grid_view,
+            move |_, _| {
+                show_duplicates_window(&window, &dir_label.label(), &images_grid_view);
+            }
+        ));
+        window.add_action(&duplicates_action);
+
+        let duplicates_button = Button::builder()
+            .icon_name("edit-copy-symbolic")
+            .action_name("win.find-duplicates")
+            .tooltip_text("Find Duplicates")
+            .build();
+        header_box.append(&duplicates_button);
+
+        let details_panel = DetailsPanel::new();
+        images_grid_view.connect_selection_changed(clone!(
+            #[strong]
+            details_panel,
+            move |item| {
+                details_panel.show(item.as_ref());
+            }
+        ));
+
+        let details_action = SimpleAction::new_stateful("toggle-details", None, &true.to_variant());
+        details_action.connect_activate(clone!(
+            #[strong]
+            details_panel,
+            move |action, _| {
+                let visible = !action.state().and_then(|state| state.get::<bool>()).unwrap_or(true);
+                action.set_state(&visible.to_variant());
+                details_panel.widget.set_visible(visible);
+            }
+        ));
+        window.add_action(&details_action);
+
+        let details_button = ToggleButton::builder()
+            .icon_name("sidebar-show-right-symbolic")
+            .action_name("win.toggle-details")
+            .tooltip_text("Image Details (F9)")
+            .build();
+        header_box.append(&details_button);
+
+        let profiles_menu = ProfilesMenu::new(window);
+        header_box.append(&profiles_menu.widget);
+
+        let shortcuts_action = SimpleAction::new("show-shortcuts", None);
+        shortcuts_action.connect_activate(clone!(
+            #[weak]
+            window,
+            move |_, _| {
+                show_shortcuts_window(&window);
+            }
+        ));
+        window.add_action(&shortcuts_action);
+
+        let shortcuts_button = Button::builder()
+            .icon_name("preferences-desktop-keyboard-shortcuts-symbolic")
+            .action_name("win.show-shortcuts")
+            .tooltip_text("Keyboard Shortcuts")
+            .build();
+        header_box.append(&shortcuts_button);
+
+        if let Some(app) = window.application() {
+            app.set_accels_for_action("win.browse", &["<Control>o"]);
+            app.set_accels_for_action("win.show-shortcuts", &["<Control>question", "F1"]);
+            app.set_accels_for_action("win.toggle-details", &["F9"]);
+            app.set_accels_for_action("win.preferences", &["<Control>comma"]);
+        }
+
         main_box.append(&header_box);
 
-        main_box.append(&images_grid_view.widget);
+        let diagnostics_banner = DiagnosticsBanner::new();
+        main_box.append(&diagnostics_banner.widget);
+
+        let grid_controls = GridControls::new(&images_grid_view);
+        main_box.append(&grid_controls.widget);
+
+        // Bubble phase, so text typed into the search entry never reaches these keys.
+        let key_controller = EventControllerKey::new();
+        key_controller.connect_key_pressed(clone!(
+            #[strong(rename_to = search_entry)]
+            grid_controls.search_entry,
+            #[strong]
+            monitor_bar,
+            move |_, key, _, modifiers| {
+                if modifiers.intersects(ModifierType::CONTROL_MASK | ModifierType::ALT_MASK | ModifierType::SUPER_MASK) {
+                    return Propagation::Proceed;
+                }
+                if key == Key::slash {
+                    search_entry.grab_focus();
+                    return Propagation::Stop;
+                }
+                if let Some(digit) = key.to_unicode().and_then(|c| c.to_digit(10))
+                    && monitor_bar.activate_nth(digit as usize)
+                {
+                    return Propagation::Stop;
+                }
+                Propagation::Proceed
+            }
+        ));
+        window.add_controller(key_controller);
 
-        if has_wallpapers() {
-            on_dir_selected(get_first_wallpaper_path().as_str(), dir_label, images_grid_view.images_path_list);
+        main_box.append(&preview_bar.widget);
+
+        let paned = Paned::builder()
+            .orientation(Orientation::Horizontal)
+            .start_child(&images_grid_view.widget)
+            .end_child(&details_panel.widget)
+            .resize_end_child(false)
+            .shrink_end_child(false)
+            .vexpand(true)
+            .build();
+        main_box.append(&paned);
+
+        let initial_dir = get_settings()
+            .last_directory
+            .filter(|dir| Path::new(dir).is_dir())
+            .or_else(|| has_wallpapers().then(get_first_wallpaper_path));
+        if let Some(dir) = initial_dir {
+            on_dir_selected(&dir, dir_label, images_grid_view.clone());
         }
+        images_grid_view.grab_focus();
 
         Self { widget: main_box }
     }
 }
 
-fn on_dir_selected(path: &str, dir_label: Label, images_path_list: StringList) {
-    dir_label.set_label(path);
+// Dropping onto a monitor toggle targets that monitor, anywhere else targets the selected one.
+fn wallpaper_drop_target(window: &ApplicationWindow, monitor: Option<String>, dir_label: &Label, preview_bar: &PreviewBar) -> DropTarget {
+    let drop_target = DropTarget::new(FileList::static_type(), DragAction::COPY);
+    drop_target.connect_drop(clone!(
+        #[strong]
+        dir_label,
+        #[strong]
+        preview_bar,
+        #[weak]
+        window,
+        #[upgrade_or]
+        false,
+        move |_, value, _, _| {
+            let Ok(file_list) = value.get::<FileList>() else {
+                return false;
+            };
 
-    images_path_list.splice(0, images_path_list.n_items(), &[] as &[&str]);
-
-    for image in read_image_entries(path) {
-        images_path_list.append(image.as_str());
-    }
+            // Copying can take a while for large or remote files, the window stays responsive meanwhile.
+            let files = file_list.files();
+            let current_dir = dir_label.label().to_string();
+            glib::spawn_future_local(clone!(
+                #[strong]
+                monitor,
+                #[strong]
+                preview_bar,
+                async move {
+                    if let Some(path) = import_first_image(files, current_dir).await {
+                        apply_wallpaper(&window, &preview_bar, monitor.unwrap_or_else(get_selected_monitor), path);
+                    }
+                }
+            ));
+            true
+        }
+    ));
+    drop_target
 }
 
-fn read_image_entries(path: &str) -> Vec<String> {
-    let entries = match fs::read_dir(path) {
-        Ok(entries) => entries,
-        Err(_) => return Vec::new(),
-    };
-
-    let mut files = Vec::new();
+// Edits made by hand to hyprpaper.conf or a file it sources, or by `ultrapaper` run from a terminal,
+// are picked up, the model tells the views.
+fn watch_config_files(window: &ApplicationWindow) {
+    let file_monitors: Rc<RefCell<Vec<(PathBuf, FileMonitor)>>> = Rc::default();
+    update_watched_files(&file_monitors);
+    app_model().connect_wallpaper_changed(clone!(
+        #[weak]
+        file_monitors,
+        move |_| update_watched_files(&file_monitors)
+    ));
+    // The monitors stop watching once dropped, the window keeps them.
+    window.connect_destroy(move |_| file_monitors.borrow_mut().clear());
+}
 
-    for entry in entries {
-        let entry = match entry {
-            Ok(entry) => entry,
-            Err(_) => continue,
-        };
+// Follows the files the config sources as they are added and removed.
+fn update_watched_files(file_monitors: &RefCell<Vec<(PathBuf, FileMonitor)>>) {
+    let mut paths = app_model().read_config(|config| config.file_paths());
+    if paths.is_empty() {
+        paths.push(HyprpaperConfig::get_default_config_path());
+    }
 
-        if entry.file_type().map_or(true, |ft| ft.is_dir()) {
+    let mut file_monitors = file_monitors.borrow_mut();
+    file_monitors.retain(|(path, _)| paths.contains(path));
+    for path in paths {
+        if file_monitors.iter().any(|(watched, _)| *watched == path) {
             continue;
         }
+        match gio::File::for_path(&path).monitor_file(gio::FileMonitorFlags::NONE, Cancellable::NONE) {
+            Ok(file_monitor) => {
+                file_monitor.connect_changed(|_, _, _, event| {
+                    if event == gio::FileMonitorEvent::ChangesDoneHint {
+                        reload_config();
+                    }
+                });
+                file_monitors.push((path, file_monitor));
+            }
+            Err(err) => eprintln!("Could not watch {}: {}", path.display(), err),
+        }
+    }
+}
 
-        let metadata = match entry.metadata() {
-            Ok(metadata) => metadata,
-            Err(_) => continue,
-        };
+fn reload_config() {
+    match HyprpaperConfig::new(HyprpaperConfig::get_default_config_path()) {
+        // What the app saved itself is already in the model.
+        Ok(config) if app_model().read_config(|current| current.reads_the_same_as(&config)) => {}
+        Ok(config) => set_config(config),
+        Err(err) => eprintln!("Error reloading config: {}", err),
+    }
+}
 
-        if !metadata.is_file() {
-            continue;
+// Hyprland tells when a monitor is plugged in or out, or its config was reloaded with a monitor
+// perhaps rotated or scaled, only then is the list read again.
+fn watch_monitors() {
+    glib::spawn_future_local(async {
+        let listening = events::listen(|event| {
+            if matches!(event, "monitoradded" | "monitorremoved" | "configreloaded") {
+                refresh_monitors();
+            }
+        });
+        if let Err(err) = listening.await {
+            eprintln!("{}, monitor changes won't be noticed", err);
+        }
+    });
+}
+
+fn refresh_monitors() {
+    glib::spawn_future_local(async {
+        if let Ok(Ok(monitors)) = gio::spawn_blocking(try_get_monitors).await
+            && monitors != get_monitor_details()
+        {
+            set_monitors(monitors);
         }
+    });
+}
 
-        let file_name = entry.file_name();
-        let file_name_str = match file_name.to_str() {
-            Some(name) => name,
-            None => continue,
-        };
+const POWER_SUPPLY_POLL_SECONDS: u32 = 30;
 
-        let supported_extensions = ["jpg", "jpeg", "png", "bmp", "webp"];
-        let is_supported = file_name_str
-            .rfind('.')
-            .map(|dot_pos| {
-                let extension = &file_name_str[dot_pos + 1..];
-                supported_extensions.contains(&extension.to_lowercase().as_str())
-            })
-            .unwrap_or(false);
-
-        if is_supported {
-            let full_path = format!("{}/{}", path, file_name_str);
-            files.push(full_path);
+// Only a change is sent to the players, so pausing one by hand isn't undone on every poll. The
+// setting is read on every poll, turning it off resumes the players and turning it on takes effect
+// without a restart.
+fn watch_power_supply() {
+    let on_battery = Cell::new(false);
+    glib::timeout_add_seconds_local(POWER_SUPPLY_POLL_SECONDS, move || {
+        let now_on_battery = get_settings().video_pause_on_battery && is_on_battery();
+        if on_battery.replace(now_on_battery) != now_on_battery && mpvpaper::has_players() {
+            mpvpaper::set_paused(now_on_battery);
         }
+        ControlFlow::Continue
+    });
+}
+
+fn on_dir_selected(path: &str, dir_label: Label, images_grid_view: ImagesGridView) {
+    dir_label.set_label(path);
+    if get_settings().last_directory.as_deref() != Some(path) {
+        update_settings(|settings| settings.last_directory = Some(path.to_string()));
     }
 
-    files
+    // Every file is sniffed, so the folder is read off the UI thread and a late one is dropped.
+    let request = DIR_REQUEST.with(|last| {
+        last.set(last.get() + 1);
+        last.get()
+    });
+    let path = path.to_string();
+    glib::spawn_future_local(async move {
+        let Ok(paths) = gio::spawn_blocking(move || read_image_entries(&path)).await else {
+            return;
+        };
+        if DIR_REQUEST.with(Cell::get) == request {
+            images_grid_view.set_paths(paths);
+        }
+    });
 }
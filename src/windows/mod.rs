@@ -1 +1,118 @@
+pub mod bookmarks_menu;
+pub mod crop_window;
+pub mod duplicates_window;
+pub mod fit_warning_window;
+pub mod generator_window;
+pub mod image_actions;
 pub mod main_window;
+pub mod picker_window;
+pub mod preferences_window;
+pub mod profiles_menu;
+pub mod properties_window;
+pub mod shortcuts_window;
+
+use gio::Cancellable;
+use gio::glib::{Propagation, clone};
+use gtk::gdk::Key;
+use gtk::prelude::*;
+use gtk::{AlertDialog, Box, EventControllerKey, Grid, Orientation, Window};
+
+use crate::state::{get_monitors_showing, get_wallpapers};
+
+const DIALOG_MARGIN: i32 = 16;
+
+pub fn close_on_escape(window: &impl IsA<Window>) {
+    let window = window.upcast_ref::<Window>();
+    on_escape(window, clone!(
+        #[weak]
+        window,
+        move || window.close()
+    ));
+}
+
+pub fn on_escape(window: &impl IsA<Window>, f: impl Fn() + 'static) {
+    let key_controller = EventControllerKey::new();
+    key_controller.connect_key_pressed(move |_, key, _, _| {
+        if key != Key::Escape {
+            return Propagation::Proceed;
+        }
+        f();
+        Propagation::Stop
+    });
+    window.upcast_ref::<Window>().add_controller(key_controller);
+}
+
+// The frame every dialog's content sits in.
+pub fn dialog_box(orientation: Orientation, spacing: i32) -> Box {
+    Box::builder()
+        .orientation(orientation)
+        .spacing(spacing)
+        .margin_top(DIALOG_MARGIN)
+        .margin_bottom(DIALOG_MARGIN)
+        .margin_start(DIALOG_MARGIN)
+        .margin_end(DIALOG_MARGIN)
+        .build()
+}
+
+pub fn dialog_grid() -> Grid {
+    Grid::builder()
+        .row_spacing(8)
+        .column_spacing(16)
+        .margin_top(DIALOG_MARGIN)
+        .margin_bottom(DIALOG_MARGIN)
+        .margin_start(DIALOG_MARGIN)
+        .margin_end(DIALOG_MARGIN)
+        .build()
+}
+
+// Asks before anything that can't be undone, Cancel is the default answer.
+pub fn confirm(parent: &impl IsA<Window>, message: &str, detail: &str, accept_label: &str, on_accept: impl FnOnce() + 'static) {
+    let dialog = AlertDialog::builder()
+        .message(message)
+        .detail(detail)
+        .buttons(["Cancel", accept_label])
+        .cancel_button(0)
+        .default_button(0)
+        .modal(true)
+        .build();
+    dialog.choose(Some(parent), Cancellable::NONE, move |result| {
+        if let Ok(1) = result {
+            on_accept();
+        }
+    });
+}
+
+// hyprpaper would be left pointing at a missing file, so shown wallpapers aren't trashed.
+// Returns whether any of the paths is shown, after telling the user which.
+pub fn refuse_trashing_shown(parent: &impl IsA<Window>, paths: &[String]) -> bool {
+    let wallpapers = get_wallpapers();
+    let in_use: Vec<(&String, Vec<String>)> = paths
+        .iter()
+        .map(|path| (path, get_monitors_showing(path, &wallpapers)))
+        .filter(|(_, monitors)| !monitors.is_empty())
+        .collect();
+
+    let (message, detail) = match in_use.as_slice() {
+        [] => return false,
+        [(_, monitors)] if paths.len() == 1 => (
+            "This image is in use".to_string(),
+            format!("It is the wallpaper of {}. Set another one there before moving it to the trash.", monitors.join(", ")),
+        ),
+        _ => (
+            "Some images are in use".to_string(),
+            in_use
+                .iter()
+                .map(|(path, monitors)| format!("{} is the wallpaper of {}.", path, monitors.join(", ")))
+                .chain(["Set other wallpapers there before moving them to the trash.".to_string()])
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+    };
+    AlertDialog::builder()
+        .message(message)
+        .detail(detail)
+        .modal(true)
+        .build()
+        .show(Some(parent));
+    true
+}
@@ -0,0 +1,104 @@
+use gio::glib::{self, clone};
+use gtk::gdk;
+use gtk::prelude::*;
+use gtk::{Align, Application, ApplicationWindow, Box, Label, Orientation};
+
+use crate::hypr::hyprctl::set_wallpaper;
+use crate::images::scan::read_image_entries;
+use crate::widgets::images_grid_view::ImagesGridView;
+use crate::windows::on_escape;
+
+const STRIP_HEIGHT: i32 = 200;
+const PICKER_TITLE: &str = "Ultrapaper Picker";
+
+// One see-through window per output with a strip of thumbnails at the bottom, so each screen's
+// wallpaper is picked while looking at it. Picking applies right away, Escape closes every picker.
+// These are fullscreen toplevels rather than a layer-shell overlay, gtk4-layer-shell isn't a
+// dependency. The title is what a Hyprland windowrule matches them by.
+pub fn show_picker_windows(app: &Application, dir: &str) {
+    let Some(display) = gdk::Display::default() else {
+        return;
+    };
+
+    let images_grid_views: Vec<ImagesGridView> = display
+        .monitors()
+        .iter::<gdk::Monitor>()
+        .flatten()
+        .filter_map(|monitor| {
+            let connector = monitor.connector()?.to_string();
+            Some(show_picker_window(app, &monitor, connector, dir))
+        })
+        .collect();
+
+    // Every file is sniffed, so the folder is read once the pickers are up.
+    let dir = dir.to_owned();
+    glib::spawn_future_local(async move {
+        let Ok(paths) = gio::spawn_blocking(move || read_image_entries(&dir)).await else {
+            return;
+        };
+        for images_grid_view in images_grid_views {
+            images_grid_view.set_paths(paths.clone());
+        }
+    });
+}
+
+fn show_picker_window(app: &Application, monitor: &gdk::Monitor, connector: String, dir: &str) -> ImagesGridView {
+    // Escape may close the pickers before the wallpaper is converted, the process stays until it
+    // is shown and the post-apply work is done.
+    let apply = clone!(
+        #[weak]
+        app,
+        #[strong]
+        connector,
+        move |path: &str| {
+            set_wallpaper(connector.clone(), path.to_owned()).hold_application(&app);
+        }
+    );
+    let images_grid_view = ImagesGridView::new(apply.clone(), apply);
+    images_grid_view.set_single_row();
+    images_grid_view.widget.set_height_request(STRIP_HEIGHT);
+
+    let header_box = Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(12)
+        .build();
+    let title = Label::builder().label(&connector).build();
+    title.add_css_class("heading");
+    header_box.append(&title);
+    let dir_label = Label::builder().label(dir).halign(Align::Start).build();
+    dir_label.add_css_class("dim-label");
+    header_box.append(&dir_label);
+
+    let strip = Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(8)
+        .valign(Align::End)
+        .vexpand(true)
+        .build();
+    strip.add_css_class("picker-strip");
+    strip.append(&header_box);
+    strip.append(&images_grid_view.widget);
+
+    let window = ApplicationWindow::builder()
+        .application(app)
+        .title(format!("{} {}", PICKER_TITLE, connector))
+        .decorated(false)
+        .child(&strip)
+        .build();
+    window.add_css_class("picker-window");
+
+    on_escape(&window, clone!(
+        #[weak]
+        app,
+        move || {
+            for window in app.windows() {
+                window.close();
+            }
+        }
+    ));
+
+    window.fullscreen_on_monitor(monitor);
+    window.present();
+    images_grid_view.grab_focus();
+    images_grid_view
+}
@@ -0,0 +1,194 @@
+use std::path::Path;
+
+use gio::Cancellable;
+use gio::glib::clone;
+use gtk::prelude::*;
+use gtk::{
+    Align, ApplicationWindow, Box, Button, DropDown, FileDialog, Grid, Label, ListBox, Orientation, SelectionMode, SpinButton, Switch, Window
+};
+
+use crate::hypr::hyprpaper::FitMode;
+use crate::hypr::mpvpaper;
+use crate::settings::{MAX_THUMBNAIL_SIZE, MIN_THUMBNAIL_SIZE, MonitorIdentifier, Settings, VideoBackend, get_settings, update_settings};
+use crate::state::{migrate_monitor_identifiers, save_config};
+use crate::widgets::grid_controls::bind_thumbnail_size;
+use crate::windows::{close_on_escape, dialog_box};
+
+const THUMBNAIL_SIZE_STEP: f64 = 20.0;
+
+type Toggle = (&'static str, bool, fn(&mut Settings, bool));
+
+// Changes are saved to ultrapaper.conf as they are made.
+pub fn show_preferences_window(parent: &ApplicationWindow) {
+    let settings = get_settings();
+
+    let grid = Grid::builder().row_spacing(8).column_spacing(16).build();
+    let mut row = 0;
+    let mut add_row = |name: &str, widget: &gtk::Widget| {
+        let label = Label::builder().label(name).halign(Align::Start).hexpand(true).build();
+        grid.attach(&label, 0, row, 1, 1);
+        widget.set_halign(Align::End);
+        grid.attach(widget, 1, row, 1, 1);
+        row += 1;
+    };
+
+    let thumbnail_size = SpinButton::with_range(MIN_THUMBNAIL_SIZE as f64, MAX_THUMBNAIL_SIZE as f64, THUMBNAIL_SIZE_STEP);
+    bind_thumbnail_size(&thumbnail_size.adjustment());
+    add_row("Thumbnail size", thumbnail_size.upcast_ref());
+
+    let fit_mode = DropDown::from_strings(&FitMode::LABELS);
+    fit_mode.set_selected(FitMode::ALL.iter().position(|mode| *mode == settings.fit_mode).unwrap_or(0) as u32);
+    fit_mode.connect_selected_notify(|drop_down| {
+        if let Some(mode) = FitMode::ALL.get(drop_down.selected() as usize).copied() {
+            update_settings(|settings| settings.fit_mode = mode);
+        }
+    });
+    add_row("Default fit mode", fit_mode.upcast_ref());
+
+    let video_backend = DropDown::from_strings(&["Play with mpvpaper", "First frame only"]);
+    video_backend.set_selected((settings.video_backend == VideoBackend::Still) as u32);
+    video_backend.connect_selected_notify(|drop_down| {
+        let backend = if drop_down.selected() == 1 { VideoBackend::Still } else { VideoBackend::Mpvpaper };
+        update_settings(|settings| settings.video_backend = backend);
+    });
+    add_row("Animated wallpapers", video_backend.upcast_ref());
+
+    let monitor_identifier = DropDown::from_strings(&["Connector name", "Description"]);
+    monitor_identifier.set_selected((settings.monitor_identifier == MonitorIdentifier::Description) as u32);
+    monitor_identifier.connect_selected_notify(|drop_down| {
+        let identifier = if drop_down.selected() == 1 { MonitorIdentifier::Description } else { MonitorIdentifier::Name };
+        update_settings(|settings| settings.monitor_identifier = identifier);
+        // Entries already in hyprpaper.conf are rewritten to the new form as well.
+        if migrate_monitor_identifiers() {
+            save_config();
+        }
+    });
+    add_row("Write monitors by", monitor_identifier.upcast_ref());
+
+    let toggles: [Toggle; 3] = [
+        ("Sync the hyprlock background", settings.hyprlock_sync, |settings, enabled| settings.hyprlock_sync = enabled),
+        ("Apply the matching profile on startup", settings.auto_profile, |settings, enabled| settings.auto_profile = enabled),
+        ("Pause videos on battery", settings.video_pause_on_battery, |settings, enabled| {
+            settings.video_pause_on_battery = enabled
+        }),
+    ];
+    for (name, active, set) in toggles {
+        let switch = Switch::builder().active(active).valign(Align::Center).build();
+        switch.connect_active_notify(move |switch| {
+            let enabled = switch.is_active();
+            update_settings(|settings| set(settings, enabled));
+        });
+        add_row(name, switch.upcast_ref());
+    }
+
+    let pause_on_fullscreen = Switch::builder()
+        .active(settings.video_pause_on_fullscreen)
+        .valign(Align::Center)
+        .build();
+    pause_on_fullscreen.connect_active_notify(|switch| {
+        let enabled = switch.is_active();
+        update_settings(|settings| settings.video_pause_on_fullscreen = enabled);
+        // mpvpaper only reads --auto-pause when it starts, running players are restarted with it.
+        mpvpaper::start_saved();
+    });
+    add_row("Pause videos behind fullscreen windows", pause_on_fullscreen.upcast_ref());
+
+    let bookmarks_title = Label::builder().label("Bookmarked folders").halign(Align::Start).build();
+    bookmarks_title.add_css_class("heading");
+
+    let bookmarks_list = ListBox::builder().selection_mode(SelectionMode::None).build();
+    bookmarks_list.add_css_class("boxed-list");
+    fill_bookmarks_list(&bookmarks_list);
+
+    let add_bookmark_button = Button::builder().label("Add Folder…").halign(Align::Start).build();
+
+    let content = dialog_box(Orientation::Vertical, 12);
+    content.append(&grid);
+    content.append(&bookmarks_title);
+    content.append(&bookmarks_list);
+    content.append(&add_bookmark_button);
+
+    let window = Window::builder()
+        .title("Preferences")
+        .transient_for(parent)
+        .modal(true)
+        .resizable(false)
+        .default_width(460)
+        .child(&content)
+        .build();
+
+    add_bookmark_button.connect_clicked(clone!(
+        #[weak]
+        window,
+        #[weak]
+        bookmarks_list,
+        move |_| {
+            FileDialog::builder().title("Bookmark a Folder").modal(true).build().select_folder(
+                Some(&window),
+                Cancellable::NONE,
+                move |result| {
+                    if let Some(path) = result.ok().and_then(|folder| folder.path()) {
+                        add_bookmark(&path.to_string_lossy());
+                        fill_bookmarks_list(&bookmarks_list);
+                    }
+                },
+            );
+        }
+    ));
+
+    close_on_escape(&window);
+
+    window.present();
+}
+
+pub fn add_bookmark(path: &str) {
+    if get_settings().bookmarks.iter().any(|bookmark| bookmark == path) {
+        return;
+    }
+    update_settings(|settings| settings.bookmarks.push(path.to_string()));
+}
+
+fn fill_bookmarks_list(bookmarks_list: &ListBox) {
+    bookmarks_list.remove_all();
+
+    let bookmarks = get_settings().bookmarks;
+    bookmarks_list.set_visible(!bookmarks.is_empty());
+    for bookmark in bookmarks {
+        let row = Box::builder()
+            .orientation(Orientation::Horizontal)
+            .spacing(8)
+            .margin_top(4)
+            .margin_bottom(4)
+            .margin_start(8)
+            .margin_end(4)
+            .build();
+        let label = Label::builder()
+            .label(&bookmark)
+            .tooltip_text(&bookmark)
+            .halign(Align::Start)
+            .hexpand(true)
+            .ellipsize(gtk::pango::EllipsizeMode::Middle)
+            .build();
+        if !Path::new(&bookmark).is_dir() {
+            label.add_css_class("dim-label");
+        }
+        row.append(&label);
+
+        let remove_button = Button::builder()
+            .icon_name("list-remove-symbolic")
+            .tooltip_text("Remove Bookmark")
+            .build();
+        remove_button.add_css_class("flat");
+        remove_button.connect_clicked(clone!(
+            #[weak]
+            bookmarks_list,
+            move |_| {
+                update_settings(|settings| settings.bookmarks.retain(|other| *other != bookmark));
+                fill_bookmarks_list(&bookmarks_list);
+            }
+        ));
+        row.append(&remove_button);
+
+        bookmarks_list.append(&row);
+    }
+}
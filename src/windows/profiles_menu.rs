@@ -0,0 +1,143 @@
+use gio::{Menu, MenuItem, SimpleAction};
+use gio::glib::{self, VariantTy, clone};
+use gtk::prelude::*;
+use gtk::{Application, ApplicationWindow, Button, Entry, MenuButton, Orientation, Window};
+
+use crate::profiles::{apply_profile, delete_profile, list_profiles, save_profile};
+use crate::state::get_wallpapers;
+use crate::windows::{close_on_escape, dialog_box};
+
+// An app action, so `org.gtk.Actions.Activate` on the session bus can switch profiles too.
+pub fn add_profile_actions(app: &Application) {
+    let apply_profile_action = SimpleAction::new("apply-profile", Some(VariantTy::STRING));
+    apply_profile_action.connect_activate(|_, parameter| {
+        if let Some(name) = parameter.and_then(|parameter| parameter.get::<String>())
+            && let Err(err) = apply_profile(&name)
+        {
+            eprintln!("{}", err);
+        }
+    });
+    app.add_action(&apply_profile_action);
+}
+
+pub struct ProfilesMenu {
+    pub widget: MenuButton,
+}
+
+impl ProfilesMenu {
+    pub fn new(window: &ApplicationWindow) -> Self {
+        let menu = Menu::new();
+        fill_profiles_menu(&menu);
+
+        let save_profile_action = SimpleAction::new("save-profile", None);
+        save_profile_action.connect_activate(clone!(
+            #[weak]
+            window,
+            #[strong]
+            menu,
+            move |_, _| {
+                show_save_profile_window(&window, &menu);
+            }
+        ));
+        window.add_action(&save_profile_action);
+
+        let delete_profile_action = SimpleAction::new("delete-profile", Some(VariantTy::STRING));
+        delete_profile_action.connect_activate(clone!(
+            #[strong]
+            menu,
+            move |_, parameter| {
+                if let Some(name) = parameter.and_then(|parameter| parameter.get::<String>()) {
+                    if let Err(err) = delete_profile(&name) {
+                        eprintln!("{}", err);
+                    }
+                    fill_profiles_menu(&menu);
+                }
+            }
+        ));
+        window.add_action(&delete_profile_action);
+
+        let widget = MenuButton::builder()
+            .label("Profiles")
+            .menu_model(&menu)
+            .tooltip_text("Wallpaper Profiles")
+            .build();
+
+        Self { widget }
+    }
+}
+
+fn fill_profiles_menu(menu: &Menu) {
+    menu.remove_all();
+
+    let profiles = list_profiles();
+
+    let apply_section = Menu::new();
+    for name in &profiles {
+        let item = MenuItem::new(Some(name), None);
+        item.set_action_and_target_value(Some("app.apply-profile"), Some(&name.to_variant()));
+        apply_section.append_item(&item);
+    }
+    menu.append_section(None, &apply_section);
+
+    let manage_section = Menu::new();
+    manage_section.append(Some("Save Current as Profile…"), Some("win.save-profile"));
+    if !profiles.is_empty() {
+        let delete_menu = Menu::new();
+        for name in &profiles {
+            let item = MenuItem::new(Some(name), None);
+            item.set_action_and_target_value(Some("win.delete-profile"), Some(&name.to_variant()));
+            delete_menu.append_item(&item);
+        }
+        manage_section.append_submenu(Some("Delete Profile"), &delete_menu);
+    }
+    menu.append_section(None, &manage_section);
+}
+
+fn show_save_profile_window(parent: &ApplicationWindow, menu: &Menu) {
+    let entry = Entry::builder()
+        .placeholder_text("Profile name, e.g. Docked")
+        .activates_default(true)
+        .hexpand(true)
+        .build();
+    let save_button = Button::builder().label("Save").build();
+    save_button.add_css_class("suggested-action");
+
+    let content = dialog_box(Orientation::Horizontal, 8);
+    content.append(&entry);
+    content.append(&save_button);
+
+    let window = Window::builder()
+        .title("Save Profile")
+        .transient_for(parent)
+        .modal(true)
+        .resizable(false)
+        .default_widget(&save_button)
+        .child(&content)
+        .build();
+
+    save_button.connect_clicked(clone!(
+        #[weak]
+        window,
+        #[weak]
+        entry,
+        #[strong]
+        menu,
+        move |_| {
+            let name = entry.text().trim().to_string();
+            match save_profile(&name, &get_wallpapers()) {
+                Ok(()) => {
+                    fill_profiles_menu(&menu);
+                    window.close();
+                }
+                Err(err) => {
+                    eprintln!("{}", err);
+                    entry.add_css_class("error");
+                }
+            }
+        }
+    ));
+
+    close_on_escape(&window);
+
+    window.present();
+}
@@ -0,0 +1,59 @@
+use gio::glib;
+use gtk::prelude::*;
+use gtk::{ApplicationWindow, Label, Window};
+
+use crate::images::wallpaper_item::WallpaperItem;
+use crate::windows::{close_on_escape, dialog_grid};
+
+pub fn show_properties_window(parent: &ApplicationWindow, item: &WallpaperItem) {
+    let mut rows = vec![("Name", item.name()), ("Path", item.path())];
+    if item.ensure_info() {
+        rows.push(("Dimensions", format!("{} × {}", item.width(), item.height())));
+        rows.push(("File size", glib::format_size(item.size()).to_string()));
+        rows.push(("Format", item.format().to_uppercase()));
+        rows.push((
+            "Modified",
+            glib::DateTime::from_unix_local(item.modified())
+                .and_then(|date_time| date_time.format("%c"))
+                .map(|date_time| date_time.to_string())
+                .unwrap_or_default(),
+        ));
+    }
+    let active_monitors = item.active_monitors();
+    if !active_monitors.is_empty() {
+        rows.push(("Shown on", active_monitors.join(", ")));
+    }
+
+    let grid = dialog_grid();
+
+    for (row, (name, value)) in rows.iter().enumerate() {
+        let name_label = Label::builder().label(*name).halign(gtk::Align::End).build();
+        name_label.add_css_class("dim-label");
+        grid.attach(&name_label, 0, row as i32, 1, 1);
+        grid.attach(
+            &Label::builder()
+                .label(value)
+                .halign(gtk::Align::Start)
+                .selectable(true)
+                .wrap(true)
+                .max_width_chars(60)
+                .build(),
+            1,
+            row as i32,
+            1,
+            1,
+        );
+    }
+
+    let window = Window::builder()
+        .title(format!("{} Properties", item.name()))
+        .transient_for(parent)
+        .modal(true)
+        .resizable(false)
+        .child(&grid)
+        .build();
+
+    close_on_escape(&window);
+
+    window.present();
+}
@@ -0,0 +1,48 @@
+use gtk::prelude::*;
+use gtk::{ApplicationWindow, Label, Window};
+
+use crate::windows::{close_on_escape, dialog_grid};
+
+const SHORTCUTS: [(&str, &str); 12] = [
+    ("Arrow keys", "Move through the images"),
+    ("Enter", "Apply the selected image"),
+    ("Space", "Preview the selected image"),
+    ("1 – 9", "Pick a monitor"),
+    ("0", "Pick all monitors"),
+    ("Ctrl+O", "Browse for a directory"),
+    ("/", "Search images"),
+    ("Ctrl+Scroll", "Resize the thumbnails"),
+    ("F9", "Show image details"),
+    ("Ctrl+,", "Open preferences"),
+    ("Ctrl+?", "Show keyboard shortcuts"),
+    ("Escape", "Close this window"),
+];
+
+pub fn show_shortcuts_window(parent: &ApplicationWindow) {
+    let grid = dialog_grid();
+
+    for (row, (keys, description)) in SHORTCUTS.iter().enumerate() {
+        let keys_label = Label::builder().label(*keys).halign(gtk::Align::End).build();
+        keys_label.add_css_class("keycap");
+        grid.attach(&keys_label, 0, row as i32, 1, 1);
+        grid.attach(
+            &Label::builder().label(*description).halign(gtk::Align::Start).build(),
+            1,
+            row as i32,
+            1,
+            1,
+        );
+    }
+
+    let window = Window::builder()
+        .title("Keyboard Shortcuts")
+        .transient_for(parent)
+        .modal(true)
+        .resizable(false)
+        .child(&grid)
+        .build();
+
+    close_on_escape(&window);
+
+    window.present();
+}